@@ -15,12 +15,16 @@
 //     You should have received a copy of the GNU General Public License
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::cli::{Cli, Command};
+use crate::commands;
 use crate::config::{Config, APP_NAME};
 use crate::errors::{ApcError, ApcResult};
-use crate::utils::questions::{create::post_properties, modify::modify_post_properties};
+use crate::utils::questions::{
+    create::post_properties, delete::delete_post_properties, modify::modify_post_properties,
+};
 use crate::utils::Post;
 use crate::CONFIG;
-use requestty::{prompt, Answers, Question};
+use requestty::{Answers, PromptModule, Question};
 
 #[derive(Debug)]
 pub enum Action {
@@ -30,6 +34,10 @@ pub enum Action {
         new_slug: String,
         new_image_path: String,
     },
+    Delete {
+        post: Post,
+        confirmed: bool,
+    },
     Version,
 }
 
@@ -51,6 +59,9 @@ impl TryFrom<&Answers> for Action {
         } else if action == config.select_action.update_existing_post {
             // Modify action
             Post::modify_action(config, answers)
+        } else if action == config.select_action.delete_post_choice {
+            // Delete action
+            Post::delete_action(config, answers)
         } else {
             // Version action
             Ok(Self::Version)
@@ -69,37 +80,69 @@ fn version(repo: &str) -> String {
 /// Return the questions
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn questions(config: &'static Config) -> Vec<Question> {
+fn questions(config: &'static Config) -> Vec<Question<'static>> {
     let mut questions = vec![Question::select("action")
         .message(&config.select_action.select_action_message)
         .choices(vec![
             &config.select_action.new_post_choice,
             &config.select_action.update_existing_post,
+            &config.select_action.delete_post_choice,
             &config.select_action.version_choice,
         ])
         .default(0)
         .build()];
     questions.append(&mut post_properties(config));
     questions.append(&mut modify_post_properties(config));
+    questions.append(&mut delete_post_properties(config));
     questions
 }
 
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-pub fn run(config: &'static Config) -> ApcResult<()> {
-    let answers = prompt(questions(config)).map_err(|err| {
-        log::error!("{:?}", err);
-        ApcError::Requestty(err.to_string())
-    })?;
+pub fn run(config: &'static Config, cli: &Cli) -> ApcResult<()> {
+    match &cli.command {
+        Some(Command::List(args)) => return commands::list::list(config, args),
+        Some(Command::Check) => return commands::check::check(config),
+        Some(Command::Audit(args)) => return commands::audit::audit(config, args, cli.dry_run()),
+        Some(Command::Placeholders(args)) => {
+            return commands::placeholders::placeholders(config, args, cli.dry_run())
+        }
+        Some(Command::Asset(command)) => {
+            return commands::asset::asset(config, command, cli.dry_run())
+        }
+        _ => {}
+    }
+    let answers = match &cli.command {
+        Some(command) if command.is_complete() => command.answers(config)?,
+        // The wizard will skip the answered questions, and ask only for the missing ones
+        command => PromptModule::new(questions(config))
+            .with_answers(
+                command
+                    .as_ref()
+                    .map(|command| command.answers(config))
+                    .transpose()?
+                    .unwrap_or_default(),
+            )
+            .prompt_all()
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                ApcError::Requestty(err.to_string())
+            })?,
+    };
     let action = Action::try_from(&answers)?;
     log::debug!("answers = {answers:?}\naction = {action:?}");
     match action {
-        Action::Create(post) => post.write_in_file(config)?,
+        Action::Create(mut post) => post.create_post(config, cli.dry_run())?,
         Action::Modify {
             mut new_post,
             new_slug,
             new_image_path,
-        } => new_post.modify_post(new_slug, new_image_path)?,
+        } => new_post.modify_post(new_slug, new_image_path, cli.dry_run())?,
+        Action::Delete { post, confirmed } => {
+            if confirmed {
+                post.delete(config, cli.dry_run())?
+            }
+        }
         Action::Version => {
             println!("{}", version(&config.repository_url));
         }
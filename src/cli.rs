@@ -0,0 +1,594 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use crate::utils::{parse_bool, slug_updater, to_post_path, validators, PostProperties};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use requestty::{Answer, Answers, ListItem};
+
+/// Simple CLI to create/modify aleecers post template 🦀
+///
+/// Run it without a command to use the interactive wizard
+#[derive(Parser, Debug)]
+#[command(name = "alepc", version = env!("VERGEN_GIT_SEMVER"), about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Print the filesystem changes of the wizard with a diff of the post file, without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Config file to use instead of the user and the project (`.alepc.ron`) config files
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<String>,
+    /// Blog profile to use, from `profiles` config
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Override a config key (`create_post_settings.maximum_tags_count=7`), over the config files
+    /// and the `ALEPC_*` environment variables. Can be used multiple times
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_override)]
+    pub overrides: Vec<(String, String)>,
+}
+
+/// Parse `--set` flag value to `(key, value)`
+fn parse_override(str_override: &str) -> Result<(String, String), String> {
+    str_override
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("`{str_override}` should be `KEY=VALUE`"))
+}
+
+impl Cli {
+    /// Return true if the changes should be printed only, by `--dry-run` of the wizard or the command
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+            || match &self.command {
+                Some(Command::New(args)) => args.dry_run.dry_run,
+                Some(Command::Modify(args)) => args.dry_run.dry_run,
+                Some(Command::Delete(args)) => args.dry_run.dry_run,
+                Some(Command::Audit(args)) => args.dry_run.dry_run,
+                Some(Command::Placeholders(args)) => args.dry_run.dry_run,
+                Some(Command::Asset(AssetCommand::Add(args))) => args.dry_run.dry_run,
+                _ => false,
+            }
+    }
+
+    /// Return true if the wizard will be used, there is no command or it's missing values
+    pub fn uses_wizard(&self) -> bool {
+        self.command
+            .as_ref()
+            .map_or(true, |command| !command.is_complete())
+    }
+}
+
+/// Alepc commands
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a new post, the missing values will be asked
+    New(NewArgs),
+    /// Modify an existing post
+    Modify(ModifyArgs),
+    /// Delete a post with its images directory
+    Delete(DeleteArgs),
+    /// List the posts
+    List(ListArgs),
+    /// Check all the posts and report every problem, exit with an error if there is any
+    Check,
+    /// Audit the images directory, list the orphan images and the broken images references
+    Audit(AuditArgs),
+    /// Compute the blurhash placeholders of the posts headers that don't have one
+    Placeholders(PlaceholdersArgs),
+    /// Manage the post images, other than its header
+    #[command(subcommand)]
+    Asset(AssetCommand),
+    /// Manage the config file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Print alepc version
+    Version,
+}
+
+/// `--dry-run` flag of the commands that change the files
+#[derive(Args, Debug)]
+pub struct DryRunArgs {
+    /// Print the filesystem changes with a diff of the post file, without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments of `new` command
+#[derive(Args, Debug)]
+pub struct NewArgs {
+    /// Title of the post
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Description of the post
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Tags of the post (separated by `separated_tags_by`)
+    #[arg(long)]
+    pub tags: Option<String>,
+    /// Slug of the post
+    #[arg(long)]
+    pub slug: Option<String>,
+    /// Header image of the post
+    #[arg(long)]
+    pub image: Option<String>,
+    /// Render the header card from the title and tags, instead of a header image
+    #[arg(long, conflicts_with = "image")]
+    pub card: bool,
+    /// Draft status of the post
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    pub draft: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Arguments of `modify` command
+#[derive(Args, Debug)]
+pub struct ModifyArgs {
+    /// Slug of the post to modify
+    pub slug: String,
+    /// New slug of the post
+    #[arg(long)]
+    pub new_slug: Option<String>,
+    /// New title of the post
+    #[arg(long)]
+    pub title: Option<String>,
+    /// New description of the post
+    #[arg(long)]
+    pub description: Option<String>,
+    /// New tags of the post (separated by `separated_tags_by`)
+    #[arg(long)]
+    pub tags: Option<String>,
+    /// New header image of the post
+    #[arg(long)]
+    pub image: Option<String>,
+    /// Render the header card from the (new) title and tags, as the new header
+    #[arg(long, conflicts_with = "image")]
+    pub card: bool,
+    /// New draft status of the post
+    #[arg(long)]
+    pub draft: Option<bool>,
+    /// Only update the modified date
+    #[arg(long, conflicts_with_all = ["new_slug", "title", "description", "tags", "image", "card", "draft"])]
+    pub update_date: bool,
+    /// Only toggle the draft status
+    #[arg(long, conflicts_with_all = ["new_slug", "title", "description", "tags", "image", "card", "draft"])]
+    pub toggle_draft: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Arguments of `delete` command
+#[derive(Args, Debug)]
+pub struct DeleteArgs {
+    /// Slug of the post to delete
+    pub slug: String,
+    /// Delete without confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Arguments of `list` command
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Show only the posts that have this tag (can be used multiple times)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Show only the posts with this draft status
+    #[arg(long)]
+    pub draft: Option<bool>,
+    /// Show only the posts created on or after this date (in `date_format`)
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Show only the posts created on or before this date (in `date_format`)
+    #[arg(long)]
+    pub until: Option<String>,
+    /// Sort the posts by
+    #[arg(long, value_enum, default_value_t = SortBy::Date)]
+    pub sort: SortBy,
+    /// Reverse the sort order
+    #[arg(long)]
+    pub reverse: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Arguments of `audit` command
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// Remove the orphan images without asking (moved to `trash_path` if it's configured)
+    #[arg(long)]
+    pub apply: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Arguments of `placeholders` command
+#[derive(Args, Debug)]
+pub struct PlaceholdersArgs {
+    /// Recompute the placeholders of all the posts
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Asset commands
+#[derive(Subcommand, Debug)]
+pub enum AssetCommand {
+    /// Copy the files to the post images directory, and print their markdown
+    Add(AssetAddArgs),
+}
+
+/// Arguments of `asset add` command
+#[derive(Args, Debug)]
+pub struct AssetAddArgs {
+    /// Slug of the post
+    pub slug: String,
+    /// Files to copy to the post images directory
+    #[arg(required = true)]
+    pub files: Vec<String>,
+    /// Process the images with `header_image_settings` (resize, convert and strip the metadata)
+    #[arg(long)]
+    pub process: bool,
+    /// Append the markdown of the files to the post body
+    #[arg(long)]
+    pub append: bool,
+    #[command(flatten)]
+    pub dry_run: DryRunArgs,
+}
+
+/// Config commands
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Write the config file with every key and its default value, documented
+    Init(ConfigInitArgs),
+    /// Print the effective config, after merging the config files, the profile and the overrides
+    Show(ConfigShowArgs),
+    /// Validate the effective config, and its profiles
+    Validate,
+    /// Print the paths of the config files
+    Path,
+}
+
+/// Arguments of `config init` command
+#[derive(Args, Debug)]
+pub struct ConfigInitArgs {
+    /// Overwrite the config file if it's already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments of `config show` command
+#[derive(Args, Debug)]
+pub struct ConfigShowArgs {
+    /// Print every key with its value and where it comes from (a config file, a profile, an override ..)
+    #[arg(long)]
+    pub origin: bool,
+}
+
+/// Post date to sort by
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SortBy {
+    /// Creation date
+    Date,
+    /// Last modified date
+    Modified,
+}
+
+/// Output format of the commands
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    /// Human readable table
+    Table,
+    /// JSON, for scripts
+    Json,
+}
+
+/// Run `validator` on the value of `flag`, return [`ApcError::Validation`] if it's invalid
+fn validate(
+    flag: &str,
+    value: &str,
+    answers: &Answers,
+    mut validator: impl FnMut(&str, &Answers) -> Result<(), String>,
+) -> ApcResult<()> {
+    validator(value, answers).map_err(|err| ApcError::Validation(format!("`{flag}`: {err}")))
+}
+
+/// Return list item answer
+fn list_item(index: usize, text: &str) -> Answer {
+    Answer::ListItem(ListItem {
+        index,
+        text: text.to_owned(),
+    })
+}
+
+impl NewArgs {
+    /// Return the answers of the given flags
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    fn answers(&self, config: &'static Config) -> ApcResult<Answers> {
+        let mut answers = Answers::default();
+        answers.insert(
+            "action".to_owned(),
+            list_item(0, &config.select_action.new_post_choice),
+        );
+        if let Some(title) = &self.title {
+            validate("--title", title, &answers, validators::title_length(config))?;
+            answers.insert("post_title".to_owned(), Answer::String(title.trim().into()));
+        }
+        if let Some(description) = &self.description {
+            validate(
+                "--description",
+                description,
+                &answers,
+                validators::description_length(config),
+            )?;
+            answers.insert(
+                "post_description".to_owned(),
+                Answer::String(description.trim().into()),
+            );
+        }
+        if let Some(tags) = &self.tags {
+            validate("--tags", tags, &answers, validators::tags_validator(config))?;
+            answers.insert("post_tags".to_owned(), Answer::String(tags.clone()));
+        }
+        if let Some(slug) = &self.slug {
+            validate("--slug", slug, &answers, validators::slug_lenth(config))?;
+            validate(
+                "--slug",
+                slug,
+                &answers,
+                validators::slug_path_validator(config),
+            )?;
+            answers.insert("post_slug".to_owned(), Answer::String(slug_updater(slug)));
+        }
+        if let Some(image) = &self.image {
+            validate(
+                "--image",
+                image,
+                &answers,
+                validators::header_image_validator(config),
+            )?;
+            answers.insert("post_image".to_owned(), Answer::String(image.clone()));
+            answers.insert("post_card".to_owned(), Answer::Bool(false));
+        }
+        if self.card {
+            answers.insert("post_card".to_owned(), Answer::Bool(true));
+        }
+        answers.insert("post_draft".to_owned(), Answer::Bool(self.draft));
+        Ok(answers)
+    }
+}
+
+impl ModifyArgs {
+    /// Return true if there is a post field to update
+    fn has_fields(&self) -> bool {
+        self.new_slug.is_some()
+            || self.title.is_some()
+            || self.description.is_some()
+            || self.tags.is_some()
+            || self.image.is_some()
+            || self.card
+            || self.draft.is_some()
+    }
+
+    /// Return the answers of the given flags
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    fn answers(&self, config: &'static Config) -> ApcResult<Answers> {
+        let settings = &config.modify_post_settings;
+        let mut answers = Answers::default();
+        answers.insert(
+            "action".to_owned(),
+            list_item(1, &config.select_action.update_existing_post),
+        );
+        validate(
+            "<SLUG>",
+            &self.slug,
+            &answers,
+            validators::validate_post_path_by_slug(config),
+        )?;
+        answers.insert("post_file".to_owned(), Answer::String(self.slug.clone()));
+
+        if self.update_date || self.toggle_draft {
+            let mut actions = Vec::new();
+            if self.update_date {
+                actions.push(ListItem {
+                    index: 0,
+                    text: settings.update_the_date_question.clone(),
+                });
+            }
+            if self.toggle_draft {
+                actions.push(ListItem {
+                    index: 1,
+                    text: settings.update_draft_status_question.clone(),
+                });
+            }
+            answers.insert("modify_action".to_owned(), Answer::ListItems(actions));
+        } else if self.has_fields() {
+            answers.insert(
+                "modify_action".to_owned(),
+                Answer::ListItems(vec![ListItem {
+                    index: 3,
+                    text: settings.show_all_question.clone(),
+                }]),
+            );
+            let keep = || Answer::String(settings.keep_old_value_message.clone());
+
+            if let Some(new_slug) = &self.new_slug {
+                validate(
+                    "--new-slug",
+                    new_slug,
+                    &answers,
+                    validators::slug_lenth(config),
+                )?;
+                validate(
+                    "--new-slug",
+                    new_slug,
+                    &answers,
+                    validators::slug_path_validator(config),
+                )?;
+            }
+            if let Some(title) = &self.title {
+                validate("--title", title, &answers, validators::title_length(config))?;
+            }
+            if let Some(description) = &self.description {
+                validate(
+                    "--description",
+                    description,
+                    &answers,
+                    validators::description_length(config),
+                )?;
+            }
+            if let Some(tags) = &self.tags {
+                validate("--tags", tags, &answers, validators::tags_validator(config))?;
+            }
+            if let Some(image) = &self.image {
+                validate(
+                    "--image",
+                    image,
+                    &answers,
+                    validators::header_image_validator(config),
+                )?;
+            }
+            answers.insert(
+                "new_post_slug".to_owned(),
+                self.new_slug
+                    .as_deref()
+                    .map_or_else(keep, |slug| Answer::String(slug_updater(slug))),
+            );
+            answers.insert(
+                "new_post_title".to_owned(),
+                self.title
+                    .as_deref()
+                    .map_or_else(keep, |title| Answer::String(title.trim().into())),
+            );
+            answers.insert(
+                "new_post_description".to_owned(),
+                self.description
+                    .as_deref()
+                    .map_or_else(keep, |description| {
+                        Answer::String(description.trim().into())
+                    }),
+            );
+            answers.insert(
+                "new_post_image".to_owned(),
+                self.image
+                    .as_deref()
+                    .map_or_else(keep, |image| Answer::String(image.into())),
+            );
+            answers.insert("new_post_card".to_owned(), Answer::Bool(self.card));
+            answers.insert(
+                "new_post_tags".to_owned(),
+                self.tags
+                    .as_deref()
+                    .map_or_else(keep, |tags| Answer::String(tags.into())),
+            );
+            let draft = match self.draft {
+                Some(draft) => draft,
+                None => parse_bool(
+                    &PostProperties::Draft.from_file(&to_post_path(config, &self.slug))?,
+                )?,
+            };
+            // The draft answer is whether to change the draft status,
+            // see `PostProperties::str_from_answers`
+            answers.insert("new_post_draft".to_owned(), Answer::Bool(!draft));
+        }
+        Ok(answers)
+    }
+}
+
+impl DeleteArgs {
+    /// Return the answers of the given flags
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    fn answers(&self, config: &'static Config) -> ApcResult<Answers> {
+        let mut answers = Answers::default();
+        answers.insert(
+            "action".to_owned(),
+            list_item(2, &config.select_action.delete_post_choice),
+        );
+        validate(
+            "<SLUG>",
+            &self.slug,
+            &answers,
+            validators::validate_post_path_by_slug(config),
+        )?;
+        answers.insert(
+            "delete_post_file".to_owned(),
+            Answer::String(self.slug.clone()),
+        );
+        if self.yes {
+            answers.insert("delete_confirm".to_owned(), Answer::Bool(true));
+        }
+        Ok(answers)
+    }
+}
+
+impl Command {
+    /// Return true if the command have all the values, so there is no need for the wizard
+    pub fn is_complete(&self) -> bool {
+        match self {
+            Self::New(args) => {
+                args.title.is_some()
+                    && args.description.is_some()
+                    && args.tags.is_some()
+                    && args.slug.is_some()
+                    && (args.image.is_some() || args.card)
+            }
+            Self::Modify(args) => args.update_date || args.toggle_draft || args.has_fields(),
+            Self::Delete(args) => args.yes,
+            Self::Version
+            | Self::List(_)
+            | Self::Check
+            | Self::Audit(_)
+            | Self::Placeholders(_)
+            | Self::Asset(_)
+            | Self::Config(_) => true,
+        }
+    }
+
+    /// Return the answers of the command flags, the wizard will ask for the missing ones
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn answers(&self, config: &'static Config) -> ApcResult<Answers> {
+        match self {
+            Self::New(args) => args.answers(config),
+            Self::Modify(args) => args.answers(config),
+            Self::Delete(args) => args.answers(config),
+            Self::Version => Ok(Answers::from_iter([(
+                "action".to_owned(),
+                list_item(3, &config.select_action.version_choice),
+            )])),
+            Self::List(_)
+            | Self::Check
+            | Self::Audit(_)
+            | Self::Placeholders(_)
+            | Self::Asset(_)
+            | Self::Config(_) => {
+                unreachable!("Only the posts commands use the wizard")
+            }
+        }
+    }
+}
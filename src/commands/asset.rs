@@ -0,0 +1,156 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::{AssetAddArgs, AssetCommand};
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use crate::utils::transaction::{Change, Transaction};
+use crate::utils::{
+    full_path, images, path_extension, post_images_dir, replace_tilde_with_home_dir, slug_updater,
+    to_post_path, Post,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Return the sanitized file name, lowercase ASCII letters, digits and `-` only, with the extension
+fn sanitize_name(stem: &str, extension: &str) -> String {
+    let stem = stem
+        .to_ascii_lowercase()
+        .split(|chr: char| !chr.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let stem = if stem.is_empty() { "asset" } else { &stem };
+    format!("{stem}.{}", extension.to_ascii_lowercase())
+}
+
+/// Return the changes to copy the file to the post images directory (processed if `process` is set),
+/// with the path of the copied file
+/// ### Errors
+/// * If the file doesn't exist, or cannot be processed
+fn add_changes(
+    config: &Config,
+    slug: &str,
+    file: &str,
+    process: bool,
+) -> ApcResult<(PathBuf, Change)> {
+    let file = replace_tilde_with_home_dir(file);
+    if !Path::new(&file).is_file() {
+        return Err(ApcError::Validation(format!("'{file}' is not a file")));
+    }
+    let file = full_path(&file);
+    let stem = Path::new(&file)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let images_dir = post_images_dir(config, slug);
+    let encoded = if process {
+        images::process_header(&config.header_image_settings, Path::new(&file))?.encoded
+    } else {
+        None
+    };
+    Ok(match encoded {
+        Some(image) => {
+            let path = images_dir.join(sanitize_name(&stem, image.format.extension()));
+            (path.clone(), Change::WriteImage { path, image })
+        }
+        None => {
+            let path = images_dir.join(sanitize_name(&stem, path_extension(&file)));
+            (
+                path.clone(),
+                Change::Copy {
+                    from: PathBuf::from(file),
+                    to: path,
+                },
+            )
+        }
+    })
+}
+
+/// Copy the files to the post images directory with sanitized names, and print their markdown.
+/// The markdown will be appended to the post body with `--append`. The files are copied as one unit.
+/// ## Errors:
+/// - If the post doesn't exist.
+/// - If a file doesn't exist, cannot be processed, or its name is already used in the post images directory.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn add(config: &'static Config, args: &AssetAddArgs, dry_run: bool) -> ApcResult<()> {
+    let slug = slug_updater(&args.slug);
+    let post = Post::from_file(config, to_post_path(config, &slug))?;
+    let images_dir = post_images_dir(config, &slug);
+    let mut changes = Vec::new();
+    if !images_dir.exists() {
+        changes.push(Change::CreateDir(images_dir));
+    }
+    let mut names = HashSet::new();
+    let mut markdown = Vec::new();
+    for file in &args.files {
+        let (path, change) = add_changes(config, &slug, file, args.process)?;
+        let name = path
+            .file_name()
+            .expect("The asset path is a file")
+            .to_string_lossy()
+            .into_owned();
+        // The header and its variants names are reserved
+        if path.exists()
+            || !names.insert(name.clone())
+            || name.starts_with(&format!("{slug}-header"))
+        {
+            return Err(ApcError::Validation(format!(
+                "The name `{name}` of '{file}' is already used in '{}'",
+                post_images_dir(config, &slug).display()
+            )));
+        }
+        let alt = name
+            .rsplit_once('.')
+            .map_or(name.as_str(), |(stem, _)| stem)
+            .replace('-', " ");
+        markdown.push(format!(
+            "![{alt}]({})",
+            path.to_string_lossy()
+                .replace(&config.images_path, &config.images_site_path)
+        ));
+        changes.push(change);
+    }
+    let markdown = markdown.join("\n\n");
+    if args.append {
+        let content = post.content()?;
+        let content = if content.is_empty() {
+            markdown.clone()
+        } else {
+            format!("{content}\n\n{markdown}")
+        };
+        changes.push(Change::Write {
+            path: post.path(config),
+            contents: post.file_content(config, &content),
+        });
+    }
+    Transaction::new(dry_run).apply(&changes)?;
+    if !dry_run {
+        println!("{markdown}");
+    }
+    Ok(())
+}
+
+/// Run the asset command
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn asset(config: &'static Config, command: &AssetCommand, dry_run: bool) -> ApcResult<()> {
+    match command {
+        AssetCommand::Add(args) => add(config, args, dry_run),
+    }
+}
@@ -0,0 +1,235 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::AuditArgs;
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use crate::utils::transaction::{Change, Transaction};
+use crate::utils::{links, slug_updater, Post};
+use colored::Colorize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// Return the entries of the directory, sorted
+fn read_dir(dir: &Path) -> ApcResult<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", dir.display())))?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Return the files of the directory and its sub directories
+fn files(dir: &Path) -> ApcResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in read_dir(dir)? {
+        if path.is_dir() {
+            files.append(&mut self::files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The audit result of `images_path`
+#[derive(Debug, Default)]
+struct Audit {
+    /// The directories that doesn't belong to any post, and have no referenced file
+    orphan_dirs: Vec<PathBuf>,
+    /// The files in the posts directories that are not referenced by any post
+    orphan_files: Vec<PathBuf>,
+    /// The references to missing images, the post path with the reference
+    broken_references: Vec<(PathBuf, String)>,
+}
+
+impl Audit {
+    /// Cross-reference `images_path` against the posts and the images references in them.
+    /// The files directly in `images_path` are not posts images, so they are ignored.
+    fn new(config: &'static Config) -> ApcResult<Self> {
+        let images_path = Path::new(&config.images_path);
+        let mut audit = Self::default();
+        let mut slugs = HashSet::new();
+        let mut references = HashSet::new();
+
+        for path in Post::paths(config)? {
+            // The file name is the post slug even if the post cannot be parsed
+            if let Some(stem) = path.file_stem() {
+                slugs.insert(slug_updater(&stem.to_string_lossy()));
+            }
+            let content = fs::read_to_string(&path)
+                .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+            for reference in links::image_references(config, &content) {
+                let image = images_path.join(&reference[config.images_site_path.len()..]);
+                if !image.is_file() {
+                    audit
+                        .broken_references
+                        .push((path.clone(), reference.to_owned()));
+                }
+                references.insert(image);
+            }
+        }
+
+        for dir in read_dir(images_path)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+        {
+            let files = files(&dir)?;
+            let is_post_dir = dir
+                .file_name()
+                .is_some_and(|name| slugs.contains(name.to_string_lossy().as_ref()));
+            if !is_post_dir && !files.iter().any(|file| references.contains(file)) {
+                audit.orphan_dirs.push(dir);
+            } else {
+                audit
+                    .orphan_files
+                    .extend(files.into_iter().filter(|file| !references.contains(file)));
+            }
+        }
+        Ok(audit)
+    }
+
+    /// Return the orphan directories and files
+    fn orphans(&self) -> Vec<&PathBuf> {
+        self.orphan_dirs.iter().chain(&self.orphan_files).collect()
+    }
+
+    /// Print the audit result
+    fn print(&self) {
+        let print_paths = |title: &str, paths: &[PathBuf]| {
+            if !paths.is_empty() {
+                println!("{}", title.yellow());
+                paths
+                    .iter()
+                    .for_each(|path| println!("  - {}", path.display()));
+            }
+        };
+        print_paths("Orphan directories:", &self.orphan_dirs);
+        print_paths("Orphan files:", &self.orphan_files);
+        if !self.broken_references.is_empty() {
+            println!("{}", "Broken references:".red());
+            for (path, reference) in &self.broken_references {
+                println!("  - '{}' {reference}", path.display());
+            }
+        }
+        if self.orphans().is_empty() && self.broken_references.is_empty() {
+            println!("{}", "The images directory is clean".green());
+        }
+    }
+
+    /// Return the changes to remove the orphans.
+    /// If `trash_path` is configured, will move them to `trash_path/orphan-images-<timestamp>/` instead of removing them.
+    fn cleanup_changes(&self, config: &Config) -> Vec<Change> {
+        let Some(trash_path) = &config.trash_path else {
+            return self
+                .orphan_dirs
+                .iter()
+                .map(|dir| Change::RemoveDir(dir.clone()))
+                .chain(
+                    self.orphan_files
+                        .iter()
+                        .map(|file| Change::RemoveFile(file.clone())),
+                )
+                .collect();
+        };
+        let trash_dir = Path::new(trash_path).join(format!(
+            "orphan-images-{}",
+            chrono::offset::Local::now().format("%Y%m%d%H%M%S")
+        ));
+        // The directories to create in the trash, with the orphans to move in each one
+        let mut moves: BTreeMap<PathBuf, Vec<Change>> = BTreeMap::new();
+        for orphan in self.orphans() {
+            let to = trash_dir.join(
+                orphan
+                    .strip_prefix(&config.images_path)
+                    .expect("The orphans are in the images directory"),
+            );
+            moves
+                .entry(to.parent().unwrap_or(&trash_dir).to_path_buf())
+                .or_default()
+                .push(Change::Rename {
+                    from: orphan.clone(),
+                    to,
+                });
+        }
+        let mut changes = Vec::new();
+        let mut created = HashSet::new();
+        for (dir, mut renames) in moves {
+            // Create the parents first
+            let mut dirs: Vec<&Path> = dir
+                .ancestors()
+                .take_while(|ancestor| ancestor.starts_with(&trash_dir))
+                .collect();
+            dirs.reverse();
+            for dir in dirs {
+                if created.insert(dir.to_path_buf()) {
+                    changes.push(Change::CreateDir(dir.to_path_buf()));
+                }
+            }
+            changes.append(&mut renames);
+        }
+        changes
+    }
+}
+
+/// Ask the user to confirm the cleanup, false if it's not an interactive terminal
+fn confirm_cleanup(orphans: usize) -> ApcResult<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    requestty::prompt_one(
+        requestty::Question::confirm("cleanup")
+            .message(format!("Do you want to remove the {orphans} orphans?"))
+            .default(false)
+            .build(),
+    )
+    .map(|answer| answer.as_bool().unwrap_or(false))
+    .map_err(|err| ApcError::Requestty(err.to_string()))
+}
+
+/// Audit `images_path`, print the orphan directories and files, and the broken images references.
+/// The orphans will be removed with `--apply`, or after the confirmation in interactive terminal.
+/// ## Errors:
+/// - If there is a broken reference, so it can be used in CI.
+/// - If cannot read `posts_path` or `images_path` directories.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn audit(config: &'static Config, args: &AuditArgs, dry_run: bool) -> ApcResult<()> {
+    let audit = Audit::new(config)?;
+    audit.print();
+
+    let orphans = audit.orphans().len();
+    if orphans != 0 && (args.apply || confirm_cleanup(orphans)?) {
+        Transaction::new(dry_run).apply(&audit.cleanup_changes(config))?;
+        (!dry_run).then(|| println!("The {orphans} orphans have been cleaned up"));
+    }
+
+    if audit.broken_references.is_empty() {
+        Ok(())
+    } else {
+        Err(ApcError::Validation(format!(
+            "Found {} broken images references",
+            audit.broken_references.len()
+        )))
+    }
+}
@@ -0,0 +1,237 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use crate::utils::frontmatter::{self, Frontmatter};
+use crate::utils::images::HeaderInfo;
+use crate::utils::properties::Props;
+use crate::utils::{parse_str_date, path_extension, validators, Post};
+use colored::Colorize;
+use requestty::Answers;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Push the error of the `key` value to `problems` if it's missing or invalid, or the error of its `validator`
+fn check_value<T>(
+    problems: &mut Vec<ApcError>,
+    frontmatter: &Frontmatter,
+    key: &str,
+    value: ApcResult<T>,
+    validator: impl FnOnce(T) -> Result<(), String>,
+) {
+    match value.map(validator) {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => problems.push(frontmatter.error(key, &err)),
+        Err(err) => problems.push(err),
+    }
+}
+
+/// Return the problems of the post frontmatter, each problem with its file and line.
+/// The properties are checked from the raw frontmatter, so a broken property (e.g. a missing header)
+/// doesn't hide the others
+fn frontmatter_problems(
+    config: &'static Config,
+    path: &Path,
+    frontmatter: &Frontmatter,
+) -> Vec<ApcError> {
+    let answers = Answers::default();
+    let mut problems = Vec::new();
+
+    check_value(
+        &mut problems,
+        frontmatter,
+        "title",
+        frontmatter.string("title"),
+        |title| validators::title_length(config)(&title, &answers),
+    );
+    check_value(
+        &mut problems,
+        frontmatter,
+        "description",
+        frontmatter.string("description"),
+        |description| validators::description_length(config)(&description, &answers),
+    );
+    check_value(
+        &mut problems,
+        frontmatter,
+        "tags",
+        frontmatter.strings("tags"),
+        |tags| {
+            validators::tags_validator(config)(
+                &tags.join(&config.create_post_settings.separated_tags_by.to_string()),
+                &answers,
+            )
+        },
+    );
+
+    let file_slug = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let link = format!("{}{file_slug}", config.blog_site_path);
+    check_value(
+        &mut problems,
+        frontmatter,
+        "link",
+        frontmatter.string("link"),
+        |post_link| {
+            (post_link == link)
+                .then_some(())
+                .ok_or_else(|| format!("should be `{link}` to match the file name"))
+        },
+    );
+    check_value(
+        &mut problems,
+        frontmatter,
+        "image",
+        frontmatter.string("image"),
+        |image| {
+            let header_path = Props::image_path(&image).map_err(|err| err.to_string())?;
+            if path_extension(&header_path).eq_ignore_ascii_case("svg") {
+                // The rules are for the raster headers, not the SVG header cards
+                return Ok(());
+            }
+            HeaderInfo::from_file(Path::new(&header_path))
+                .map_err(|err| err.to_string())
+                .and_then(|header| header.check(&config.header_image_rules))
+        },
+    );
+    if let Err(err) = frontmatter.bool("draft") {
+        problems.push(err);
+    }
+    let mut date = |key: &str| {
+        frontmatter
+            .string(key)
+            .and_then(|date| {
+                parse_str_date(&date, &config.date_format)
+                    .map_err(|err| frontmatter.error(key, &err.to_string()))
+            })
+            .map_err(|err| problems.push(err))
+            .ok()
+    };
+    if let (Some(date), Some(date_modified)) = (date("date"), date("dateModified")) {
+        if date_modified < date {
+            problems
+                .push(frontmatter.error("dateModified", "should not be before the post `date`"));
+        }
+    }
+    check_value(
+        &mut problems,
+        frontmatter,
+        "layout",
+        frontmatter.string("layout"),
+        |layout| {
+            (layout == config.posts_layout)
+                .then_some(())
+                .ok_or_else(|| format!("should be `{}` (`posts_layout`)", config.posts_layout))
+        },
+    );
+    problems
+}
+
+/// Return the slug of the post and its problems.
+/// The slug is from the post `link`, or from the file name if the link is invalid
+fn post_problems(config: &'static Config, path: &Path) -> (String, Vec<ApcError>) {
+    let file_slug = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            return (
+                file_slug,
+                vec![ApcError::FileSystem(format!("'{}' {err}", path.display()))],
+            )
+        }
+    };
+    let Some((source, _)) = frontmatter::split(&content) else {
+        return (
+            file_slug,
+            vec![ApcError::PostProperties(format!(
+                "'{}:1' the post should start with properties between `---` lines",
+                path.display()
+            ))],
+        );
+    };
+    match Frontmatter::parse(source, path) {
+        Ok(frontmatter) => {
+            let slug = frontmatter
+                .string("link")
+                .ok()
+                .and_then(|link| link.rsplit('/').next().map(ToOwned::to_owned))
+                .filter(|slug| !slug.is_empty())
+                .unwrap_or(file_slug);
+            (slug, frontmatter_problems(config, path, &frontmatter))
+        }
+        Err(err) => (file_slug, vec![err]),
+    }
+}
+
+/// Check all the posts of `posts_path` and print every problem found,
+/// the invalid properties, the missing header images, the headers that break `header_image_rules`,
+/// and the duplicate slugs.
+/// ## Errors:
+/// - If there is a problem in any post, so it can be used in CI.
+/// - If cannot read `posts_path` directory.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn check(config: &'static Config) -> ApcResult<()> {
+    let paths = Post::paths(config)?;
+    let mut problems = Vec::new();
+    let mut slugs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    for path in &paths {
+        let (slug, mut post_problems) = post_problems(config, path);
+        problems.append(&mut post_problems);
+        slugs.entry(slug).or_default().push(path.clone());
+    }
+    let mut duplicates: Vec<_> = slugs
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    duplicates.sort();
+    for (slug, paths) in duplicates {
+        problems.push(ApcError::PostProperties(format!(
+            "The slug `{slug}` is used by {}",
+            paths
+                .iter()
+                .map(|path| format!("'{}'", path.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    for problem in &problems {
+        problem.print();
+    }
+    if problems.is_empty() {
+        println!(
+            "{}",
+            format!("All the {} posts are valid", paths.len()).green()
+        );
+        Ok(())
+    } else {
+        Err(ApcError::Validation(format!(
+            "Found {} problems in {} posts",
+            problems.len(),
+            paths.len()
+        )))
+    }
+}
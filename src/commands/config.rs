@@ -0,0 +1,184 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::{Cli, ConfigCommand, ConfigInitArgs, ConfigShowArgs};
+use crate::config::{self, layers::Layer};
+use crate::errors::{ApcError, ApcResult};
+use colored::Colorize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Return the config file that will be written by `config init`, `--config` or the user config
+fn init_path(cli: &Cli) -> ApcResult<PathBuf> {
+    cli.config
+        .as_ref()
+        .map_or_else(config::user_config_path, |path| Ok(PathBuf::from(path)))
+}
+
+/// Write the commented default config
+/// ## Errors:
+/// - If the config file is already exists, without `--force`.
+/// - If cannot write the config file.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn init(cli: &Cli, args: &ConfigInitArgs) -> ApcResult<()> {
+    let path = init_path(cli)?;
+    if path.exists() && !args.force {
+        return Err(ApcError::Validation(format!(
+            "The config file '{}' is already exists, use `--force` to overwrite it",
+            path.display()
+        )));
+    }
+    config::write_default_config(&path, false)?;
+    println!("The config file has been written to '{}'", path.display());
+    Ok(())
+}
+
+/// Return the layer of the effective config of `profile`, without validating it
+fn effective_layer(cli: &Cli, profile: Option<&str>) -> ApcResult<Layer> {
+    config::config(cli.config.as_deref())?
+        .with_profile(profile)?
+        .with_overrides(&config::overrides(&cli.overrides))
+}
+
+/// Push the keys paths (`a.b.c`) of the values that are not structs, with their values
+fn leaves<'a>(path: String, value: &'a Value, leaves: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                self::leaves(key_path, value, leaves);
+            }
+        }
+        _ => leaves.push((path, value)),
+    }
+}
+
+/// Print the effective config, or every key with its value and origin if `--origin` is given
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn show(cli: &Cli, args: &ConfigShowArgs) -> ApcResult<()> {
+    let layer = effective_layer(cli, cli.profile.as_deref())?;
+    if !args.origin {
+        println!(
+            "{}",
+            ron::ser::to_string_pretty(&layer.config, ron::ser::PrettyConfig::new())
+                .map_err(|err| ApcError::Other(format!("Cannot print the config: {err}")))?
+        );
+        return Ok(());
+    }
+    let value = serde_json::to_value(&layer.config)
+        .map_err(|err| ApcError::Other(format!("Cannot print the config: {err}")))?;
+    let mut keys = Vec::new();
+    leaves(String::new(), &value, &mut keys);
+    let width = keys
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or_default();
+    for (key, value) in keys {
+        let origin = layer
+            .origins
+            .get(&key)
+            .map_or_else(|| "default".to_owned(), ToString::to_string);
+        println!("{key:width$} = {value}  {}", format!("# {origin}").dimmed());
+    }
+    Ok(())
+}
+
+/// Validate the effective config, and all its profiles if `--profile` is not given.
+/// The problems of every invalid one will be printed
+/// ## Errors:
+/// - If the config or a profile is invalid.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn validate(cli: &Cli) -> ApcResult<()> {
+    let layer = config::config(cli.config.as_deref())?;
+    let profiles: Vec<Option<String>> = match &cli.profile {
+        Some(profile) => vec![Some(profile.clone())],
+        None => std::iter::once(None)
+            .chain(layer.config.profiles.keys().cloned().map(Some))
+            .collect(),
+    };
+    let mut errors = Vec::new();
+    for profile in &profiles {
+        let result = effective_layer(cli, profile.as_deref())?.configuration();
+        if let Err(err) = result {
+            errors.push(match (profile, err) {
+                (Some(profile), ApcError::Validation(message)) => {
+                    ApcError::Validation(format!("`{profile}` profile: {message}"))
+                }
+                (_, err) => err,
+            });
+        }
+    }
+    if profiles.len() == 1 {
+        if let Some(err) = errors.pop() {
+            return Err(err);
+        }
+    } else if !errors.is_empty() {
+        errors.iter().for_each(ApcError::print);
+        return Err(ApcError::Validation(format!(
+            "{} of the {} configs (the config and its profiles) are invalid",
+            errors.len(),
+            profiles.len()
+        )));
+    }
+    println!("{}", "The config is valid".green());
+    Ok(())
+}
+
+/// Print the paths of the config files, in the order of their precedence
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn path(cli: &Cli) -> ApcResult<()> {
+    if let Some(path) = &cli.config {
+        println!("config:  {path}");
+        return Ok(());
+    }
+    let user_config_path = config::user_config_path()?;
+    println!(
+        "user:    {}{}",
+        user_config_path.display(),
+        if user_config_path.exists() {
+            ""
+        } else {
+            " (not found)"
+        }
+    );
+    match config::project_config_path() {
+        Some(path) => println!("project: {}", path.display()),
+        None => println!("project: {} (not found)", config::PROJECT_CONFIG_FILE),
+    }
+    Ok(())
+}
+
+/// Run the config command
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn config(cli: &Cli, command: &ConfigCommand) -> ApcResult<()> {
+    match command {
+        ConfigCommand::Init(args) => init(cli, args),
+        ConfigCommand::Show(args) => show(cli, args),
+        ConfigCommand::Validate => validate(cli),
+        ConfigCommand::Path => path(cli),
+    }
+}
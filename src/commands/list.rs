@@ -0,0 +1,145 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::{ListArgs, OutputFormat, SortBy};
+use crate::config::Config;
+use crate::errors::ApcResult;
+use crate::utils::{parse_str_date, Post};
+use serde::Serialize;
+
+/// Post row of the list output
+#[derive(Serialize, Debug)]
+struct PostRow<'a> {
+    slug: &'a str,
+    title: &'a str,
+    description: &'a str,
+    tags: &'a [String],
+    draft: bool,
+    date: String,
+    date_modified: String,
+    link: &'a str,
+    image: String,
+}
+
+impl<'a> PostRow<'a> {
+    fn new(config: &Config, post: &'a Post) -> Self {
+        Self {
+            slug: &post.slug,
+            title: &post.title,
+            description: &post.description,
+            tags: &post.tags,
+            draft: post.is_draft,
+            date: post.date.format(&config.date_format).to_string(),
+            date_modified: post.date_modified.format(&config.date_format).to_string(),
+            link: &post.link,
+            image: post
+                .image_path
+                .replace(&config.images_path, &config.images_site_path),
+        }
+    }
+}
+
+/// Print the rows as a table
+fn print_table(rows: &[PostRow]) {
+    let header = ["SLUG", "TITLE", "DATE", "MODIFIED", "DRAFT", "TAGS"];
+    let rows: Vec<[String; 6]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.slug.to_owned(),
+                row.title.to_owned(),
+                row.date.clone(),
+                row.date_modified.clone(),
+                row.draft.to_string(),
+                row.tags.join(", "),
+            ]
+        })
+        .collect();
+    let mut widths = header.map(|column| column.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let print_row = |cells: &[String]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    };
+    print_row(&header.map(String::from));
+    rows.iter().for_each(|row| print_row(row));
+}
+
+/// List the posts of `posts_path`.
+/// The posts that cannot be parsed will be reported without stopping the listing.
+/// ## Errors:
+/// - If `--since` or `--until` have an invalid date.
+/// - If cannot read `posts_path` directory.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn list(config: &'static Config, args: &ListArgs) -> ApcResult<()> {
+    let since = args
+        .since
+        .as_deref()
+        .map(|date| parse_str_date(date, &config.date_format))
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|date| parse_str_date(date, &config.date_format))
+        .transpose()?;
+    let tags: Vec<String> = args
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_ascii_lowercase())
+        .collect();
+
+    let mut posts: Vec<Post> = Post::all(config)?
+        .into_iter()
+        .filter_map(|(path, post)| post.map_err(|err| err.print_in(&path)).ok())
+        .filter(|post| {
+            tags.iter().all(|tag| post.tags.contains(tag))
+                && args.draft.map_or(true, |draft| post.is_draft == draft)
+                && since.map_or(true, |since| post.date >= since)
+                && until.map_or(true, |until| post.date <= until)
+        })
+        .collect();
+    match args.sort {
+        SortBy::Date => posts.sort_by_key(|post| post.date),
+        SortBy::Modified => posts.sort_by_key(|post| post.date_modified),
+    }
+    if args.reverse {
+        posts.reverse();
+    }
+
+    let rows: Vec<PostRow> = posts
+        .iter()
+        .map(|post| PostRow::new(config, post))
+        .collect();
+    match args.format {
+        OutputFormat::Table => print_table(&rows),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&rows).expect("The posts rows are serializable")
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::PlaceholdersArgs;
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use crate::utils::transaction::{Change, Transaction};
+use crate::utils::{card, images, path_extension, Post};
+use std::fs;
+use std::path::Path;
+
+/// Return the placeholder of the post header, the SVG header card will be rasterized
+/// ## Errors:
+/// - If the header is AVIF, it cannot be decoded, its placeholder is computed from its source on import
+fn header_placeholder(config: &Config, post: &Post) -> ApcResult<String> {
+    let extension = path_extension(&post.image_path);
+    if extension.eq_ignore_ascii_case("avif") {
+        return Err(ApcError::Validation(format!(
+            "'{}' the AVIF headers cannot be decoded, import the header again from its source to compute its placeholder (`alepc modify {} --image <SOURCE>`)",
+            post.image_path, post.slug
+        )));
+    }
+    let image = if extension.eq_ignore_ascii_case("svg") {
+        let svg = fs::read_to_string(&post.image_path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", post.image_path)))?;
+        card::rasterize(&config.header_card_settings, &svg)?
+    } else {
+        images::open(Path::new(&post.image_path))?.0
+    };
+    images::placeholder(&image)
+}
+
+/// Compute the placeholders of the posts headers that don't have one (or all of them with `--force`),
+/// and write them in `imagePlaceholder` property. The posts are written as one unit.
+/// ## Errors:
+/// - If the placeholder of a post cannot be computed, after writing the others.
+/// - If cannot read `posts_path` directory.
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn placeholders(
+    config: &'static Config,
+    args: &PlaceholdersArgs,
+    dry_run: bool,
+) -> ApcResult<()> {
+    let mut changes = Vec::new();
+    let mut failures = 0;
+    for (_, post) in Post::all(config)? {
+        let result = post.and_then(|mut post| {
+            // The placeholder of AVIF header cannot be recomputed, it's from its source
+            let is_avif = path_extension(&post.image_path).eq_ignore_ascii_case("avif");
+            if post.image_placeholder.is_some() && (!args.force || is_avif) {
+                return Ok(None);
+            }
+            let placeholder = header_placeholder(config, &post)?;
+            if post.image_placeholder.as_ref() == Some(&placeholder) {
+                return Ok(None);
+            }
+            let content = post.content()?;
+            post.image_placeholder = Some(placeholder);
+            Ok(Some(Change::Write {
+                path: post.path(config),
+                contents: post.file_content(config, &content),
+            }))
+        });
+        match result {
+            Ok(change) => changes.extend(change),
+            Err(err) => {
+                err.print();
+                failures += 1;
+            }
+        }
+    }
+
+    Transaction::new(dry_run).apply(&changes)?;
+    if !dry_run {
+        println!(
+            "The placeholders of {} posts have been updated",
+            changes.len()
+        );
+        for change in &changes {
+            if let Change::Write { path, .. } = change {
+                println!("  - {}", path.display());
+            }
+        }
+    }
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(ApcError::Validation(format!(
+            "Cannot compute the placeholders of {failures} posts"
+        )))
+    }
+}
@@ -16,273 +16,746 @@
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::errors::{ApcError, ApcResult};
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
 use directories::ProjectDirs;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use super::layers::{Layer, Override};
+use super::problems::ConfigProblem;
 
 pub const ORGANIZATION: &str = "Aleecers";
 pub const APP_NAME: &str = "alepc";
+/// The project config file name, searched upward from the current directory
+pub const PROJECT_CONFIG_FILE: &str = ".alepc.ron";
 
-/// Make if statements if (`bool_expr`) return `true` will return `Err(ApcError::Validation(`error_message`))`
+/// Push a [`ConfigProblem`] of the `keys` with `error_message` to `problems` if (`bool_expr`) return `true`
 macro_rules! validation_check {
-    ($bool_expr: expr, $error_message: expr) => {
+    ($problems: expr, [$($key: expr),+], $bool_expr: expr, $error_message: expr) => {
         if $bool_expr {
-            return Err(ApcError::Validation($error_message.to_string()));
+            $problems.push(ConfigProblem::new(&[$($key),+], $error_message));
         }
     };
-    ($bool_expr: expr, $error_message: expr, $($bool_exprs: expr, $error_messages: expr),+) => {
-        validation_check!($bool_expr, $error_message);
-        validation_check!($($bool_exprs, $error_messages),+)
-    };
 }
 
 macro_rules! validate_configuration_path {
-    ($path: expr, $path_name: ident, $config_message: expr, $check_is_dir: expr) => {
+    ($problems: expr, $path: expr, $path_name: ident, $check_is_dir: expr) => {
+        let path_name = stringify!($path_name);
+        let path = $path;
+        if !Path::new(path).exists() {
+            $problems.push(ConfigProblem::new(
+                &[path_name],
+                format!("Invalid `{path_name}` '{path}' does not exist"),
+            ));
+        } else {
+            validation_check!(
+                $problems,
+                [path_name],
+                if $check_is_dir {
+                    !Path::new(path).is_dir()
+                } else {
+                    !Path::new(path).is_file()
+                },
+                format!(
+                    "Invalid `{path_name}` `{path}` it's not {path_type}",
+                    path_type = if $check_is_dir { "directory" } else { "file" }
+                )
+            );
+        }
+    };
+}
+
+macro_rules! validate_configuration_slashes {
+    ($problems: expr, $value: expr, $name: ident) => {
+        let name = stringify!($name);
+        let value = $value;
         validation_check!(
-            !Path::new($path).exists(),
-            format!(
-                "Invalid `{p_n}` '{p}' does not exist. Update `{p_n}` from config file{c}",
-                p_n = stringify!($path_name),
-                p = $path,
-                c = $config_message,
-            )
+            $problems,
+            [name],
+            !value.starts_with('/'),
+            format!("Invalid `{name}` '{value}' must starts with a slash")
+        );
+        validate_configuration_slashes!($problems, value, $name, end);
+    };
+    ($problems: expr, $value: expr, $name: ident, end) => {
+        let name = stringify!($name);
+        let value = $value;
+        validation_check!(
+            $problems,
+            [name],
+            !value.ends_with('/'),
+            format!("Invalid `{name}` '{value}' must ends with a slash")
         );
+    };
+}
 
+/// Push a [`ConfigProblem`] if the `minimum` key of the section is greater than its `maximum` key
+macro_rules! validate_configuration_range {
+    ($problems: expr, $section: ident, $minimum: ident, $maximum: ident) => {
+        let section = stringify!($section);
+        let (minimum, maximum) = (stringify!($minimum), stringify!($maximum));
         validation_check!(
-            if $check_is_dir {
-                !Path::new($path).is_dir()
-            } else {
-                !Path::new($path).is_file()
-            },
+            $problems,
+            [
+                &format!("{section}.{minimum}"),
+                &format!("{section}.{maximum}")
+            ],
+            $section.$minimum > $section.$maximum,
             format!(
-                "Invalid `{p_n}` `{p}` it's not {path_type}{c}",
-                p_n = stringify!($path_name),
-                p = $path,
-                c = $config_message,
-                path_type = if $check_is_dir { "directory" } else { "file" }
+                "Invalid `{section}` the `{minimum}` ({}) is greater than `{maximum}` ({})",
+                $section.$minimum, $section.$maximum
             )
-        )
+        );
     };
 }
 
-macro_rules! validate_configuration_slashes {
-    ($value: expr, $name: ident, $config_message: expr) => {
-        let name = stringify!($name);
-        let config_message = $config_message;
-        let value = $value;
-        validation_check!(
-            !$value.ends_with('/'),
-            format!("Invalid `{name}` '{value}' must ends with a slash {config_message}",),
-            !$value.starts_with('/'),
-            format!("Invalid `{name}` '{value}' must starts with a slash {config_message}",)
-        )
+/// Declare a config section structure, with the documentation of its keys in `DOCS`.
+/// The first doc comment line of each field is its documentation, so it's written once
+macro_rules! config_section {
+    (
+        $(#[$struct_attr: meta])*
+        pub struct $name: ident {
+            $(
+                #[doc = $doc: literal]
+                $(#[$field_attr: meta])*
+                pub $field: ident: $field_type: ty,
+            )*
+        }
+    ) => {
+        $(#[$struct_attr])*
+        pub struct $name {
+            $(
+                #[doc = $doc]
+                $(#[$field_attr])*
+                pub $field: $field_type,
+            )*
+        }
+
+        impl $name {
+            /// The documentation of the section keys, by their names
+            pub const DOCS: &'static [(&'static str, &'static str)] =
+                &[$((stringify!($field), $doc)),*];
+        }
     };
 }
 
-/// Select action configuration structure
-#[derive(Deserialize, Debug, Educe)]
-#[educe(Default)]
-#[serde(default)]
-pub struct SelectAction {
-    /// The question of select action
-    #[educe(Default = "What do you want to do ❓")]
-    pub select_action_message: String,
-    /// Create a new post choice in select
-    #[educe(Default = "Create a new post ✍")]
-    pub new_post_choice: String,
-    /// Update an existing post choice
-    #[educe(Default = "Update existing post 🖌️")]
-    pub update_existing_post: String,
-    /// Version choice
-    #[educe(Default = "Alepc Version ⚙")]
-    pub version_choice: String,
+/// Return the problem of the date format, if the formatted dates cannot be parsed back to the same date
+fn date_format_problem(date_format: &str) -> Option<String> {
+    let items: Vec<_> = StrftimeItems::new(date_format).collect();
+    if items.contains(&Item::Error) {
+        return Some(format!(
+            "Invalid `date_format` '{date_format}' it's not a valid format"
+        ));
+    }
+    // The day is greater than 12, so the day and the month cannot be swapped
+    let date = NaiveDate::from_ymd_opt(2022, 11, 23).expect("The date is valid");
+    let formatted = date.format_with_items(items.iter()).to_string();
+    match NaiveDate::parse_from_str(&formatted, date_format) {
+        Ok(parsed) if parsed == date => None,
+        Ok(parsed) => Some(format!(
+            "Invalid `date_format` '{date_format}' the date {date} is written as `{formatted}` and read back as {parsed}"
+        )),
+        Err(err) => Some(format!(
+            "Invalid `date_format` '{date_format}' the date {date} is written as `{formatted}` and cannot be read back: {err}"
+        )),
+    }
 }
 
-/// Inputs setting structure
-#[derive(Deserialize, Debug, Educe)]
-#[educe(Default)]
-#[serde(default)]
-pub struct CreatePostSettings {
-    /// Minimum length of post title
-    #[educe(Default = 7)]
-    pub minimum_title_length: u8,
-    /// Maximum length of post title
-    #[educe(Default = 30)]
-    pub maximum_title_length: u8,
-    /// Ask for post title message
-    #[educe(Default = "Title of post 📝")]
-    pub title_message: String,
-
-    /// Minimum length of post description
-    #[educe(Default = 10)]
-    pub minimum_description_length: u8,
-    /// Maximum length of post description
-    #[educe(Default = 255)]
-    pub maximum_description_length: u8,
-    /// Ask for post description message
-    #[educe(Default = "Description of post 📝")]
-    pub description_message: String,
-
-    /// Minimum tags on post
-    #[educe(Default = 1)]
-    pub minimum_tags_count: u8,
-    /// Maximum tags on post
-    #[educe(Default = 3)]
-    pub maximum_tags_count: u8,
-    /// Ask for post tags message
-    #[educe(Default = "Tags of post (separated by comma)")]
-    pub tags_message: String,
-    /// separated tags by
-    #[educe(Default = ',')]
-    pub separated_tags_by: char,
-    /// Minimum single tag length
-    #[educe(Default = 3)]
-    pub minimum_single_tag_length: u8,
-    /// Maximum single tag length
-    #[educe(Default = 8)]
-    pub maximum_single_tag_length: u8,
-
-    /// Ask for post slug message
-    #[educe(Default = "Slug of post")]
-    pub slug_message: String,
-    /// Minimum length of post slug
-    #[educe(Default = 5)]
-    pub minimum_slug_length: u8,
-    /// Maximum length of post slug
-    #[educe(Default = 20)]
-    pub maximum_slug_length: u8,
-
-    /// Ask for post image message
-    #[educe(Default = "Image of post")]
-    pub image_message: String,
+/// Image format of the processed images
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Webp,
+    Avif,
+    Jpeg,
+    Png,
 }
 
-#[derive(Deserialize, Debug, Educe)]
-#[educe(Default)]
-#[serde(default)]
-pub struct ModifyPostSettings {
-    /// The question of post name
-    #[educe(Default = "What's the post you want to modify it (Write the slug)")]
-    pub post_name_question: String,
-    /// Choice modify action
-    #[educe(Default = "What do you want to update?")]
-    pub choice_action: String,
-    /// Update date question
-    #[educe(Default = "Update modified date")]
-    pub update_the_date_question: String,
-    /// Update draft status question ( Will add the currently status in the end)
-    #[educe(Default = "Update draft status")]
-    pub update_draft_status_question: String,
-    /// Show all fields to update it question
-    #[educe(Default = "Show all")]
-    pub show_all_question: String,
-    /// New post slug question (Wheen show_all)
-    #[educe(Default = "New post slug")]
-    pub new_post_slug: String,
-    /// New post title question (Wheen show_all)
-    #[educe(Default = "New post title")]
-    pub new_post_title: String,
-    /// New post descripation question (Wheen show_all)
-    #[educe(Default = "New post description")]
-    pub new_post_descrioption: String,
-    /// New post image question (Wheen show_all)
-    #[educe(Default = "New post image")]
-    pub new_post_image: String,
-    /// New post tags question (Wheen show_all)
-    #[educe(Default = "New post tags")]
-    pub new_post_tags: String,
-    /// New post draft status question (Wheen show_all)
-    #[educe(Default = "Do you want to change draft status?")]
-    pub new_post_draft: String,
-    /// Message to keep old value
-    #[educe(Default = "Press enter to keep it 🤏")]
-    pub keep_old_value_message: String,
+config_section! {
+    /// Header image processing settings structure
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct HeaderImageSettings {
+        /// Maximum width of the header, the larger header will be resized (keeping its aspect ratio)
+        pub max_width: Option<u32>,
+        /// Maximum height of the header, the larger header will be resized (keeping its aspect ratio)
+        pub max_height: Option<u32>,
+        /// Format to re-encode the header to, `None` to keep its format
+        pub format: Option<ImageFormat>,
+        /// Quality of the re-encoded header, from 1 to 100 (PNG is lossless)
+        #[educe(Default = 80)]
+        pub quality: u8,
+        /// Strip the metadata (EXIF, GPS ..) by re-encoding the header
+        pub strip_metadata: bool,
+        /// Widths of the responsive variants of the header, `{slug}-header-{width}.{extension}`
+        pub variants: Vec<u32>,
+        /// Compute a blurhash placeholder of the header, in `imagePlaceholder` post property
+        pub placeholder: bool,
+    }
+}
+
+config_section! {
+    /// Header image validation rules structure
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct HeaderImageRules {
+        /// Allowed formats of the header, empty to allow all the formats
+        pub formats: Vec<ImageFormat>,
+        /// Minimum width of the header
+        pub min_width: Option<u32>,
+        /// Minimum height of the header
+        pub min_height: Option<u32>,
+        /// Maximum width of the header
+        pub max_width: Option<u32>,
+        /// Maximum height of the header
+        pub max_height: Option<u32>,
+        /// Aspect ratio of the header (width / height)
+        pub aspect_ratio: Option<f32>,
+        /// Allowed difference from `aspect_ratio`, `0.05` means 5%
+        #[educe(Default = 0.05)]
+        pub aspect_ratio_tolerance: f32,
+        /// Maximum size of the header in bytes
+        pub max_bytes: Option<u64>,
+    }
+}
+
+/// Format of the header card
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardFormat {
+    /// Vector card, written as it is
+    Svg,
+    /// Rendered card, processed with `header_image_settings` like the header images
+    Png,
+}
+
+config_section! {
+    /// Header card settings structure, the card is rendered from the post title and tags
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct HeaderCardSettings {
+        /// Format of the card
+        #[educe(Default(expression = "CardFormat::Png"))]
+        pub format: CardFormat,
+        /// Width of the card
+        #[educe(Default = 1200)]
+        pub width: u32,
+        /// Height of the card
+        #[educe(Default = 630)]
+        pub height: u32,
+        /// Background color of the card
+        #[educe(Default = "#1e1e2e")]
+        pub background: String,
+        /// Color of the title
+        #[educe(Default = "#cdd6f4")]
+        pub foreground: String,
+        /// Color of the tags and the side bar
+        #[educe(Default = "#fab387")]
+        pub accent: String,
+        /// Font family of the card text
+        #[educe(Default = "sans-serif")]
+        pub font_family: String,
+        /// Font file to render the card with, in addition to the system fonts
+        pub font_path: Option<String>,
+        /// Logo image of the card (PNG, JPEG, GIF, WebP or SVG)
+        pub logo_path: Option<String>,
+        /// SVG template of the card, `None` to use the default one
+        pub template_path: Option<String>,
+    }
+}
+
+config_section! {
+    /// Select action configuration structure
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct SelectAction {
+        /// The question of select action
+        #[educe(Default = "What do you want to do ❓")]
+        pub select_action_message: String,
+        /// Create a new post choice in select
+        #[educe(Default = "Create a new post ✍")]
+        pub new_post_choice: String,
+        /// Update an existing post choice
+        #[educe(Default = "Update existing post 🖌️")]
+        pub update_existing_post: String,
+        /// Delete a post choice
+        #[educe(Default = "Delete a post 🗑️")]
+        pub delete_post_choice: String,
+        /// Version choice
+        #[educe(Default = "Alepc Version ⚙")]
+        pub version_choice: String,
+        /// The question of select profile, asked when there is profiles
+        #[educe(Default = "Which blog do you want to use? 📚")]
+        pub select_profile_message: String,
+        /// The choice to not use a profile
+        #[educe(Default = "Default")]
+        pub default_profile_choice: String,
+    }
+}
+
+config_section! {
+    /// Inputs setting structure
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct CreatePostSettings {
+        /// Minimum length of post title
+        #[educe(Default = 7)]
+        pub minimum_title_length: u8,
+        /// Maximum length of post title
+        #[educe(Default = 30)]
+        pub maximum_title_length: u8,
+        /// Ask for post title message
+        #[educe(Default = "Title of post 📝")]
+        pub title_message: String,
+
+        /// Minimum length of post description
+        #[educe(Default = 10)]
+        pub minimum_description_length: u8,
+        /// Maximum length of post description
+        #[educe(Default = 255)]
+        pub maximum_description_length: u8,
+        /// Ask for post description message
+        #[educe(Default = "Description of post 📝")]
+        pub description_message: String,
+
+        /// Minimum tags on post
+        #[educe(Default = 1)]
+        pub minimum_tags_count: u8,
+        /// Maximum tags on post
+        #[educe(Default = 3)]
+        pub maximum_tags_count: u8,
+        /// Ask for post tags message
+        #[educe(Default = "Tags of post (separated by comma)")]
+        pub tags_message: String,
+        /// separated tags by
+        #[educe(Default = ',')]
+        pub separated_tags_by: char,
+        /// Minimum single tag length
+        #[educe(Default = 3)]
+        pub minimum_single_tag_length: u8,
+        /// Maximum single tag length
+        #[educe(Default = 8)]
+        pub maximum_single_tag_length: u8,
+
+        /// Ask for post slug message
+        #[educe(Default = "Slug of post")]
+        pub slug_message: String,
+        /// Minimum length of post slug
+        #[educe(Default = 5)]
+        pub minimum_slug_length: u8,
+        /// Maximum length of post slug
+        #[educe(Default = 20)]
+        pub maximum_slug_length: u8,
+
+        /// Ask for post image message
+        #[educe(Default = "Image of post")]
+        pub image_message: String,
+        /// Ask to render the header card instead of the header image message
+        #[educe(Default = "Render a header card from the title and tags instead of an image? 🎨")]
+        pub card_message: String,
+    }
+}
+
+config_section! {
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct ModifyPostSettings {
+        /// The question of post name
+        #[educe(Default = "What's the post you want to modify it (Write the slug)")]
+        pub post_name_question: String,
+        /// Choice modify action
+        #[educe(Default = "What do you want to update?")]
+        pub choice_action: String,
+        /// Update date question
+        #[educe(Default = "Update modified date")]
+        pub update_the_date_question: String,
+        /// Update draft status question ( Will add the currently status in the end)
+        #[educe(Default = "Update draft status")]
+        pub update_draft_status_question: String,
+        /// Show all fields to update it question
+        #[educe(Default = "Show all")]
+        pub show_all_question: String,
+        /// New post slug question (When show_all)
+        #[educe(Default = "New post slug")]
+        pub new_post_slug: String,
+        /// New post title question (When show_all)
+        #[educe(Default = "New post title")]
+        pub new_post_title: String,
+        /// New post description question (When show_all)
+        #[educe(Default = "New post description")]
+        pub new_post_descrioption: String,
+        /// New post image question (When show_all)
+        #[educe(Default = "New post image")]
+        pub new_post_image: String,
+        /// New post tags question (When show_all)
+        #[educe(Default = "New post tags")]
+        pub new_post_tags: String,
+        /// New post draft status question (When show_all)
+        #[educe(Default = "Do you want to change draft status?")]
+        pub new_post_draft: String,
+        /// Message to keep old value
+        #[educe(Default = "Press enter to keep it 🤏")]
+        pub keep_old_value_message: String,
+    }
 }
 
-/// Config structure for Alepc
-#[derive(Deserialize, Debug, Educe)]
+config_section! {
+    /// Delete post settings structure
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct DeletePostSettings {
+        /// The question of post name
+        #[educe(Default = "What's the post you want to delete it (Write the slug)")]
+        pub post_name_question: String,
+        /// Confirm the deletion question
+        #[educe(Default = "Are you sure you want to delete the post and its images?")]
+        pub confirm_question: String,
+    }
+}
+
+/// Blog profile structure, only its written keys will override the global ones
+#[derive(Deserialize, Serialize, Debug, Educe)]
 #[educe(Default)]
 #[serde(default)]
-pub struct Config {
+pub struct Profile {
     /// Path of posts
-    #[educe(Default = "../Aleecers.github.io/src/pages/blog/")]
-    pub posts_path: String,
+    pub posts_path: Option<String>,
     /// Path to images directory
-    #[educe(Default = "../Aleecers.github.io/public/images/")]
-    pub images_path: String,
+    pub images_path: Option<String>,
     /// Path of blog in the site
-    #[educe(Default = "/blog/")]
-    pub blog_site_path: String,
+    pub blog_site_path: Option<String>,
     /// Path of images in the site
-    #[educe(Default = "/images/")]
-    pub images_site_path: String,
+    pub images_site_path: Option<String>,
     /// Layout path of posts ( path start from `posts_path` )
-    #[educe(Default = "../../layouts/blog.astro")]
-    pub posts_layout: String,
-    /// Repository url
-    #[educe(Default = "https://github.com/aleecers/alepc")]
-    pub repository_url: String,
-    /// Date format
-    #[educe(Default = "%Y/%m/%d")]
-    pub date_format: String,
-    /// Select action structure
-    pub select_action: SelectAction,
-    /// Creat post setting
+    pub posts_layout: Option<String>,
+    /// Create post setting
     pub create_post_settings: CreatePostSettings,
-    /// Modify post setting
-    pub modify_post_settings: ModifyPostSettings,
+}
+
+config_section! {
+    /// Config structure for Alepc
+    #[derive(Deserialize, Serialize, Debug, Educe)]
+    #[educe(Default)]
+    #[serde(default)]
+    pub struct Config {
+        /// Path of posts
+        #[educe(Default = "../Aleecers.github.io/src/pages/blog/")]
+        pub posts_path: String,
+        /// Path to images directory
+        #[educe(Default = "../Aleecers.github.io/public/images/")]
+        pub images_path: String,
+        /// Header image processing settings
+        pub header_image_settings: HeaderImageSettings,
+        /// Header image validation rules
+        pub header_image_rules: HeaderImageRules,
+        /// Header card settings
+        pub header_card_settings: HeaderCardSettings,
+        /// Path of blog in the site
+        #[educe(Default = "/blog/")]
+        pub blog_site_path: String,
+        /// Path of images in the site
+        #[educe(Default = "/images/")]
+        pub images_site_path: String,
+        /// Layout path of posts ( path start from `posts_path` )
+        #[educe(Default = "../../layouts/blog.astro")]
+        pub posts_layout: String,
+        /// Repository url
+        #[educe(Default = "https://github.com/aleecers/alepc")]
+        pub repository_url: String,
+        /// Date format, it should have the year, the month and the day, so the dates can be read back
+        #[educe(Default = "%Y/%m/%d")]
+        pub date_format: String,
+        /// Path of trash directory, the deleted posts will be moved to it instead of removing them
+        pub trash_path: Option<String>,
+        /// Path of redirects file (JSON), the old link of the renamed posts will be redirected to the new one in it
+        pub redirects_path: Option<String>,
+        /// Select action structure
+        pub select_action: SelectAction,
+        /// Create post setting
+        pub create_post_settings: CreatePostSettings,
+        /// Modify post setting
+        pub modify_post_settings: ModifyPostSettings,
+        /// Delete post setting
+        pub delete_post_settings: DeletePostSettings,
+        /// Blog profiles, selected with `--profile` or in the wizard
+        pub profiles: BTreeMap<String, Profile>,
+    }
 }
 
 impl Config {
-    /// Return the configuration if it's valid
+    /// Return all the problems of the configuration, empty if it's valid
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
-    pub fn configuration(self) -> ApcResult<Self> {
-        let config_issue = "\n\tsee: <https://github.com/Aleecers/alepc/issues/2>";
-        validate_configuration_path!(&self.posts_path, posts_path, config_issue, true);
-        validate_configuration_path!(&self.images_path, images_path, config_issue, true);
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        validate_configuration_path!(problems, &self.posts_path, posts_path, true);
+        validate_configuration_path!(problems, &self.images_path, images_path, true);
+        let posts_layout = Path::new(&self.posts_path).join(&self.posts_layout);
         validate_configuration_path!(
-            Path::new(&self.posts_path)
-                .join(&self.posts_layout)
-                .to_str()
-                .unwrap(),
+            problems,
+            posts_layout.to_str().unwrap(),
             posts_layout,
-            config_issue,
             false
         );
-        validate_configuration_slashes!(&self.blog_site_path, blog_site_path, config_issue);
-        validate_configuration_slashes!(&self.images_site_path, images_site_path, config_issue);
-        Ok(self)
+        if let Some(trash_path) = &self.trash_path {
+            validation_check!(
+                problems,
+                ["trash_path"],
+                Path::new(trash_path).exists() && !Path::new(trash_path).is_dir(),
+                format!("Invalid `trash_path` `{trash_path}` it's not directory")
+            );
+        }
+        if let Some(redirects_path) = &self.redirects_path {
+            validation_check!(
+                problems,
+                ["redirects_path"],
+                Path::new(redirects_path).exists() && !Path::new(redirects_path).is_file(),
+                format!("Invalid `redirects_path` `{redirects_path}` it's not file")
+            );
+        }
+        let settings = &self.header_image_settings;
+        validation_check!(
+            problems,
+            ["header_image_settings.quality"],
+            !(1..=100).contains(&settings.quality),
+            format!(
+                "Invalid `header_image_settings.quality` `{}` it should be from 1 to 100",
+                settings.quality
+            )
+        );
+        validation_check!(
+            problems,
+            [
+                "header_image_settings.max_width",
+                "header_image_settings.max_height"
+            ],
+            settings.max_width == Some(0) || settings.max_height == Some(0),
+            "Invalid `header_image_settings` the maximum width and height cannot be 0"
+        );
+        validation_check!(
+            problems,
+            ["header_image_settings.variants"],
+            settings.variants.contains(&0),
+            "Invalid `header_image_settings.variants` the variant width cannot be 0"
+        );
+        let rules = &self.header_image_rules;
+        validation_check!(
+            problems,
+            [
+                "header_image_rules.min_width",
+                "header_image_rules.max_width"
+            ],
+            rules
+                .min_width
+                .zip(rules.max_width)
+                .is_some_and(|(min, max)| min > max),
+            "Invalid `header_image_rules` the `min_width` is greater than `max_width`"
+        );
+        validation_check!(
+            problems,
+            [
+                "header_image_rules.min_height",
+                "header_image_rules.max_height"
+            ],
+            rules
+                .min_height
+                .zip(rules.max_height)
+                .is_some_and(|(min, max)| min > max),
+            "Invalid `header_image_rules` the `min_height` is greater than `max_height`"
+        );
+        validation_check!(
+            problems,
+            ["header_image_rules.aspect_ratio"],
+            rules.aspect_ratio.is_some_and(|ratio| ratio <= 0.0),
+            "Invalid `header_image_rules.aspect_ratio` it should be greater than 0"
+        );
+        validation_check!(
+            problems,
+            ["header_image_rules.aspect_ratio_tolerance"],
+            rules.aspect_ratio_tolerance < 0.0,
+            "Invalid `header_image_rules.aspect_ratio_tolerance` it cannot be negative"
+        );
+        let card = &self.header_card_settings;
+        validation_check!(
+            problems,
+            ["header_card_settings.width", "header_card_settings.height"],
+            card.width == 0 || card.height == 0,
+            "Invalid `header_card_settings` the width and height cannot be 0"
+        );
+        for (name, path) in [
+            ("font_path", &card.font_path),
+            ("logo_path", &card.logo_path),
+            ("template_path", &card.template_path),
+        ] {
+            if let Some(path) = path {
+                validation_check!(
+                    problems,
+                    [&format!("header_card_settings.{name}")],
+                    !Path::new(path).is_file(),
+                    format!("Invalid `header_card_settings.{name}` `{path}` it's not file")
+                );
+            }
+        }
+        validate_configuration_slashes!(problems, &self.blog_site_path, blog_site_path);
+        validate_configuration_slashes!(problems, &self.images_site_path, images_site_path);
+        let create_post_settings = &self.create_post_settings;
+        validate_configuration_range!(
+            problems,
+            create_post_settings,
+            minimum_title_length,
+            maximum_title_length
+        );
+        validate_configuration_range!(
+            problems,
+            create_post_settings,
+            minimum_description_length,
+            maximum_description_length
+        );
+        validate_configuration_range!(
+            problems,
+            create_post_settings,
+            minimum_tags_count,
+            maximum_tags_count
+        );
+        validate_configuration_range!(
+            problems,
+            create_post_settings,
+            minimum_single_tag_length,
+            maximum_single_tag_length
+        );
+        validate_configuration_range!(
+            problems,
+            create_post_settings,
+            minimum_slug_length,
+            maximum_slug_length
+        );
+        if let Some(problem) = date_format_problem(&self.date_format) {
+            problems.push(ConfigProblem::new(&["date_format"], problem));
+        }
+        problems
     }
 }
 
-/// Return config
+/// Return the user config path, in the config system directory
+/// ### Errors
+/// * If the config system directory is not found
+pub fn user_config_path() -> ApcResult<PathBuf> {
+    ProjectDirs::from("", ORGANIZATION, APP_NAME)
+        .map(|path| path.config_dir().join("config.ron"))
+        .ok_or_else(|| ApcError::FileSystem("Can't get config path".to_string()))
+}
+
+/// Write the documented default config to `path`, with its missing directories.
+/// The keys will be commented out with `comment_values`, so the defaults are not written
+/// ### Errors
+/// * If cannot create the directories, or write the file
+pub fn write_default_config(path: &Path, comment_values: bool) -> ApcResult<()> {
+    let file_error =
+        |err: std::io::Error| ApcError::FileSystem(format!("'{}' {err}", path.display()));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(file_error)?;
+    }
+    fs::write(path, super::template::commented_config(comment_values)).map_err(file_error)
+}
+
+/// Return the nearest project config file, searched upward from the current directory
+pub fn project_config_path() -> Option<PathBuf> {
+    std::env::current_dir().ok().and_then(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Return the config layer of the file, with its relative paths resolved from its directory
+fn file_layer(path: &Path) -> ApcResult<Layer> {
+    let mut layer = Layer::from_file(path)?;
+    layer.resolve_paths(path.parent().unwrap_or(Path::new(".")));
+    Ok(layer)
+}
+
+/// Return config layers.
+/// The project config (`.alepc.ron`) is layered over the user config, only its written keys
+/// will override the user config. If `config_path` is given, it will be used instead of both.
+/// The missing user config will be written with the keys commented out, so they keep their defaults
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-pub fn config() -> ApcResult<Config> {
-    let config_path = ProjectDirs::from("", ORGANIZATION, APP_NAME)
-        .map(|path| path.config_dir().join("config.ron"))
-        .ok_or_else(|| ApcError::FileSystem("Can't get config path".to_string()))?;
-
-    if config_path.exists() {
-        match fs::read_to_string(config_path) {
-            Ok(str_ron) => ron::from_str(&str_ron).map_err(|err| ApcError::ParseRon {
-                position: err.position,
-                code: err,
-            }),
-            Err(err) => Err(ApcError::FileSystem(err.to_string())),
-        }
+pub fn config(config_path: Option<&str>) -> ApcResult<Layer> {
+    if let Some(config_path) = config_path {
+        return file_layer(Path::new(config_path))?.over(Layer::default());
+    }
+    let user_config_path = user_config_path()?;
+
+    let user_config = if user_config_path.exists() {
+        Layer::from_file(&user_config_path)?
     } else {
-        fs::write(config_path, "(\n    \n)")
-            .map_err(|err| ApcError::FileSystem(err.to_string()))?;
-        Ok(Config::default())
+        write_default_config(&user_config_path, true)?;
+        Layer::default()
+    };
+    match project_config_path() {
+        Some(project_config_path) => file_layer(&project_config_path)?.over(user_config),
+        None => Ok(user_config),
     }
 }
 
-/// Return [`Config`]
+/// Return the overrides of the config keys, the `ALEPC_*` environment variables then the `--set` flags
+pub fn overrides(flags: &[(String, String)]) -> Vec<Override> {
+    Override::from_env()
+        .into_iter()
+        .chain(flags.iter().map(Override::from_flag))
+        .collect()
+}
+
+/// Ask for the profile to use, `None` for the default choice
+/// ### Errors
+/// * If the prompt failed
+fn select_profile(config: &Config) -> ApcResult<Option<String>> {
+    let choices: Vec<_> = std::iter::once(&config.select_action.default_profile_choice)
+        .chain(config.profiles.keys())
+        .collect();
+    requestty::prompt_one(
+        requestty::Question::select("profile")
+            .message(&config.select_action.select_profile_message)
+            .choices(choices)
+            .default(0)
+            .build(),
+    )
+    .map(|answer| {
+        answer
+            .as_list_item()
+            .filter(|item| item.index != 0)
+            .map(|item| item.text.clone())
+    })
+    .map_err(|err| ApcError::Requestty(err.to_string()))
+}
+
+/// Return [`Config`] of `config_path` (or the user and the project config files), with the profile
+/// and the `--set` flags overrides written over it.
+/// The profile will be asked if `uses_wizard` is true, there is profiles and `profile` is not given
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-pub fn get_config() -> ApcResult<Config> {
-    config()?.configuration()
+pub fn get_config(
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    uses_wizard: bool,
+    flags: &[(String, String)],
+) -> ApcResult<Config> {
+    let layer = config(config_path)?;
+    let profile = match profile {
+        Some(profile) => Some(profile.to_owned()),
+        None if uses_wizard && !layer.config.profiles.is_empty() => select_profile(&layer.config)?,
+        None => None,
+    };
+    layer
+        .with_profile(profile.as_deref())?
+        .with_overrides(&overrides(flags))?
+        .configuration()
 }
@@ -0,0 +1,531 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::problems::{self, Source};
+use super::Config;
+use crate::errors::{ApcError, ApcResult};
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the environment variables that override the config keys
+pub const ENV_PREFIX: &str = "ALEPC_";
+
+/// The keys that are written in a config file, to layer only them over the lower config
+#[derive(Debug, Default, Clone)]
+pub struct Keys(BTreeMap<String, Option<Keys>>);
+
+/// A key of the config file, a struct field (identifier) or a map key (string)
+struct Key(String);
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a config key")
+            }
+
+            fn visit_str<E: de::Error>(self, key: &str) -> Result<Key, E> {
+                Ok(Key(key.to_owned()))
+            }
+        }
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+/// A value of the config file, `Some` if it's a struct with keys.
+/// The values are ignored, only their keys are needed
+struct KeysValue(Option<Keys>);
+
+impl<'de> Deserialize<'de> for KeysValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = KeysValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a config value")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeysValue, A::Error> {
+                let mut keys = BTreeMap::new();
+                while let Some((Key(key), KeysValue(value))) = map.next_entry()? {
+                    keys.insert(key, value);
+                }
+                Ok(KeysValue(Some(Keys(keys))))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeysValue, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(KeysValue(None))
+            }
+
+            fn visit_some<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<KeysValue, D::Error> {
+                KeysValue::deserialize(deserializer)
+            }
+
+            fn visit_none<E>(self) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            // The enum variants are units
+            fn visit_unit<E>(self) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_i64<E>(self, _: i64) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_u64<E>(self, _: u64) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_char<E>(self, _: char) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+
+            fn visit_str<E>(self, _: &str) -> Result<KeysValue, E> {
+                Ok(KeysValue(None))
+            }
+        }
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Return the RON options of the config files,
+/// `implicit_some` to write the optional keys without `Some(..)`
+pub fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+/// Where a config key value comes from
+#[derive(Debug, Clone)]
+pub enum Origin {
+    /// A config file
+    File(PathBuf),
+    /// A profile of a config file, its key is written over the global one
+    Profile { name: String, path: PathBuf },
+    /// An `ALEPC_*` environment variable
+    Env(String),
+    /// A `--set` flag
+    Flag(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "'{}'", path.display()),
+            Self::Profile { name, path } => write!(f, "`{name}` profile in '{}'", path.display()),
+            Self::Env(name) => write!(f, "`{name}` environment variable"),
+            Self::Flag(key) => write!(f, "`--set {key}` flag"),
+        }
+    }
+}
+
+/// An override of a config key, from an environment variable or a `--set` flag
+#[derive(Debug)]
+pub struct Override {
+    /// The key path (`create_post_settings.maximum_tags_count`)
+    pub key: String,
+    /// The RON value, the strings can be written without quotes
+    pub value: String,
+    pub origin: Origin,
+}
+
+impl Override {
+    /// Return the overrides of the `ALEPC_*` environment variables, sorted by their names.
+    /// The nested keys are separated by `__` (`ALEPC_CREATE_POST_SETTINGS__MAXIMUM_TAGS_COUNT`)
+    pub fn from_env() -> Vec<Self> {
+        let mut overrides: Vec<_> = std::env::vars()
+            .filter_map(|(name, value)| {
+                let key = name
+                    .strip_prefix(ENV_PREFIX)?
+                    .to_lowercase()
+                    .replace("__", ".");
+                Some(Self {
+                    key,
+                    value,
+                    origin: Origin::Env(name),
+                })
+            })
+            .collect();
+        overrides.sort_by(|a, b| a.key.cmp(&b.key));
+        overrides
+    }
+
+    /// Return the override of a `--set` flag
+    pub fn from_flag((key, value): &(String, String)) -> Self {
+        Self {
+            key: key.clone(),
+            value: value.clone(),
+            origin: Origin::Flag(key.clone()),
+        }
+    }
+
+    /// Return true if the key is not in `config`, the `profiles` keys are known (they cannot be overridden)
+    fn is_unknown(&self, config: &Value) -> bool {
+        let mut segments = self.key.split('.');
+        segments.clone().next() != Some("profiles")
+            && segments
+                .try_fold(config, |value, segment| value.get(segment))
+                .is_none()
+    }
+
+    /// Return the override as a layer, its value is parsed as it is, then as a string and as a char.
+    /// `config` is the config to override, its keys are the known keys
+    /// ### Errors
+    /// * If the key is unknown, it's a profile key, or the value is invalid
+    fn layer(&self, config: &Value) -> ApcResult<Layer> {
+        let segments: Vec<&str> = self.key.split('.').collect();
+        if segments[0] == "profiles" {
+            return Err(ApcError::Validation(format!(
+                "The `profiles` keys cannot be overridden ({}), select the profile with `--profile` then override the global keys",
+                self.origin
+            )));
+        }
+        if self.is_unknown(config) {
+            return Err(ApcError::Validation(format!(
+                "Unknown config key `{}` in {}",
+                self.key, self.origin
+            )));
+        }
+        let parse = |value: &str| -> Result<Layer, ron::error::SpannedError> {
+            let str_ron = segments
+                .iter()
+                .rev()
+                .fold(value.to_owned(), |value, key| format!("({key}: {value})"));
+            let config = ron_options().from_str(&str_ron)?;
+            let keys = ron_options()
+                .from_str::<KeysValue>(&str_ron)?
+                .0
+                .unwrap_or_default();
+            Ok(Layer {
+                config,
+                origins: keys
+                    .leaves()
+                    .into_iter()
+                    .map(|key| (key, self.origin.clone()))
+                    .collect(),
+                keys,
+                sources: Vec::new(),
+            })
+        };
+        // Only the key should be written, not the other keys after the value
+        let is_key = |layer: &Layer| {
+            layer.origins.keys().all(|key| {
+                key == &self.key
+                    || key
+                        .strip_prefix(&self.key)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        };
+        let mut chars = self.value.chars();
+        let char_value = chars.next().filter(|_| chars.next().is_none());
+        let err = match parse(&self.value) {
+            Ok(layer) if is_key(&layer) => return Ok(layer),
+            Ok(_) => None,
+            Err(err) => Some(err.code),
+        };
+        [
+            ron::to_string(&self.value).ok(),
+            char_value.and_then(|chr| ron::to_string(&chr).ok()),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|value| parse(&value).ok().filter(is_key))
+        .ok_or_else(|| {
+            ApcError::Validation(format!(
+                "Invalid `{}` value `{}` in {}{}",
+                self.key,
+                self.value,
+                self.origin,
+                err.map(|err| format!(": {err}")).unwrap_or_default()
+            ))
+        })
+    }
+}
+
+/// A parsed config file, or the merge of config files and overrides
+#[derive(Debug, Default)]
+pub struct Layer {
+    /// The config of the file, the missing keys have the default values
+    pub config: Config,
+    /// The keys that are written in the file (or the files)
+    pub keys: Keys,
+    /// The origin of the written keys (`a.b.c`), the others have the default values
+    pub origins: BTreeMap<String, Origin>,
+    /// The config files, from the lower to the upper, to show the problems snippets
+    pub sources: Vec<Source>,
+}
+
+impl Layer {
+    /// Parse the config file
+    /// ### Errors
+    /// * If the file cannot be read, or it's not a valid config
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn from_file(path: &Path) -> ApcResult<Self> {
+        let str_ron = fs::read_to_string(path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+        let parse_error = |err| problems::parse_error(path, &str_ron, &err);
+        let config = ron_options().from_str(&str_ron).map_err(parse_error)?;
+        let keys = ron_options()
+            .from_str::<KeysValue>(&str_ron)
+            .map_err(parse_error)?
+            .0
+            .unwrap_or_default();
+        Ok(Self {
+            config,
+            origins: keys
+                .leaves()
+                .into_iter()
+                .map(|key| (key, Origin::File(path.to_owned())))
+                .collect(),
+            keys,
+            sources: vec![Source {
+                path: path.to_owned(),
+                text: str_ron,
+            }],
+        })
+    }
+
+    /// Resolve the relative paths of the file (`posts_path`, `images_path` ..) from `dir`
+    pub fn resolve_paths(&mut self, dir: &Path) {
+        let resolve = |path: &mut String| {
+            if Path::new(path.as_str()).is_relative() && !path.starts_with('~') {
+                // `join` keeps the trailing slash of the directories paths
+                *path = dir
+                    .join(path.strip_prefix("./").unwrap_or(path))
+                    .to_string_lossy()
+                    .into_owned();
+            }
+        };
+        let config = &mut self.config;
+        resolve(&mut config.posts_path);
+        resolve(&mut config.images_path);
+        let card = &mut config.header_card_settings;
+        [
+            &mut config.trash_path,
+            &mut config.redirects_path,
+            &mut card.font_path,
+            &mut card.logo_path,
+            &mut card.template_path,
+        ]
+        .into_iter()
+        .chain(
+            config
+                .profiles
+                .values_mut()
+                .flat_map(|profile| [&mut profile.posts_path, &mut profile.images_path]),
+        )
+        .flatten()
+        .for_each(resolve);
+    }
+
+    /// Write the keys of the layer over the `lower` layer, the result has the keys of both
+    /// ### Errors
+    /// * If the configs cannot be merged
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn over(self, lower: Layer) -> ApcResult<Layer> {
+        let mut keys = lower.keys;
+        keys.extend(&self.keys);
+        let mut origins = lower.origins;
+        origins.extend(self.origins);
+        let mut sources = lower.sources;
+        sources.extend(self.sources);
+        Ok(Layer {
+            config: merge_config(&lower.config, to_value(&self.config)?, &self.keys)?,
+            keys,
+            origins,
+            sources,
+        })
+    }
+
+    /// Return the layer with the profile keys written over its config, the layer itself if there is no profile
+    /// ### Errors
+    /// * If there is no profile with this name
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn with_profile(mut self, name: Option<&str>) -> ApcResult<Layer> {
+        let Some(name) = name else {
+            return Ok(self);
+        };
+        let profile = self.config.profiles.get(name).ok_or_else(|| {
+            ApcError::Validation(format!(
+                "There is no `{name}` profile, the profiles: {}",
+                self.config
+                    .profiles
+                    .keys()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?;
+        if let Some(keys) = self
+            .keys
+            .get("profiles")
+            .and_then(|profiles| profiles.get(name))
+        {
+            self.config = merge_config(&self.config, to_value(profile)?, keys)?;
+            for key in keys.leaves() {
+                if let Some(Origin::File(path)) =
+                    self.origins.get(&format!("profiles.{name}.{key}"))
+                {
+                    let origin = Origin::Profile {
+                        name: name.to_owned(),
+                        path: path.clone(),
+                    };
+                    self.origins.insert(key, origin);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Write the overrides over the layer, in their order.
+    /// The unknown keys of the environment variables are skipped with a warning
+    /// ### Errors
+    /// * If a `--set` flag key is unknown, or an override value is invalid
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn with_overrides(self, overrides: &[Override]) -> ApcResult<Layer> {
+        overrides.iter().try_fold(self, |layer, config_override| {
+            let config = to_value(&layer.config)?;
+            // The environment can have other `ALEPC_*` variables, only the flags keys should be known
+            if matches!(config_override.origin, Origin::Env(_))
+                && config_override.is_unknown(&config)
+            {
+                log::warn!(
+                    "Unknown config key `{}` in {}, it's ignored",
+                    config_override.key,
+                    config_override.origin
+                );
+                return Ok(layer);
+            }
+            config_override.layer(&config)?.over(layer)
+        })
+    }
+
+    /// Return the config if it's valid, `posts_path` and `images_path` will end with a slash
+    /// ### Errors
+    /// * If the config has problems, all of them with the snippets of their keys
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn configuration(mut self) -> ApcResult<Config> {
+        // The posts and images paths are joined with the file names as strings
+        for path in [&mut self.config.posts_path, &mut self.config.images_path] {
+            if !path.ends_with('/') {
+                path.push('/');
+            }
+        }
+        let problems = self.config.problems();
+        if problems.is_empty() {
+            return Ok(self.config);
+        }
+        Err(ApcError::Validation(problems::render(
+            &problems,
+            &self.sources,
+            &self.origins,
+        )))
+    }
+}
+
+impl Keys {
+    /// Return the keys of the struct value of `key`
+    fn get(&self, key: &str) -> Option<&Keys> {
+        self.0.get(key).and_then(Option::as_ref)
+    }
+
+    /// Return the paths (`a.b.c`) of the keys that have values, not structs
+    fn leaves(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .flat_map(|(key, keys)| match keys {
+                Some(keys) => keys
+                    .leaves()
+                    .into_iter()
+                    .map(|leaf| format!("{key}.{leaf}"))
+                    .collect(),
+                None => vec![key.clone()],
+            })
+            .collect()
+    }
+
+    /// Add the `other` keys to the keys
+    fn extend(&mut self, other: &Keys) {
+        for (key, other_keys) in &other.0 {
+            match (self.0.get_mut(key), other_keys) {
+                (Some(Some(keys)), Some(other_keys)) => keys.extend(other_keys),
+                _ => {
+                    self.0.insert(key.clone(), other_keys.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Return the JSON value of the config (or a part of it), to be merged
+fn to_value(value: &impl Serialize) -> ApcResult<Value> {
+    serde_json::to_value(value)
+        .map_err(|err| ApcError::Other(format!("Cannot layer the config: {err}")))
+}
+
+/// Return the `lower` config with the `keys` of `upper` written over it
+fn merge_config(lower: &Config, upper: Value, keys: &Keys) -> ApcResult<Config> {
+    fn merge(lower: &mut Value, upper: Value, keys: &Keys) {
+        let (Value::Object(lower), Value::Object(mut upper)) = (lower, upper) else {
+            return;
+        };
+        for (key, sub_keys) in &keys.0 {
+            let Some(value) = upper.remove(key) else {
+                continue;
+            };
+            match (lower.get_mut(key), sub_keys) {
+                (Some(lower_value), Some(sub_keys)) => merge(lower_value, value, sub_keys),
+                _ => {
+                    lower.insert(key.clone(), value);
+                }
+            }
+        }
+    }
+    let mut value = to_value(lower)?;
+    merge(&mut value, upper, keys);
+    serde_json::from_value(value)
+        .map_err(|err| ApcError::Other(format!("Cannot layer the config: {err}")))
+}
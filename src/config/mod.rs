@@ -16,5 +16,8 @@
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod config_utils;
+pub mod layers;
+pub mod problems;
+pub mod template;
 
 pub use config_utils::*;
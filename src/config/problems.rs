@@ -0,0 +1,503 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::layers::Origin;
+use crate::errors::ApcError;
+use colored::Colorize;
+use ron::error::SpannedError;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The config issue, printed after the config problems
+pub const CONFIG_ISSUE: &str = "see: <https://github.com/Aleecers/alepc/issues/2>";
+
+/// A problem of the config, with the keys that cause it
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    /// The keys path of the problem (`create_post_settings.maximum_tags_count`),
+    /// the snippet of each written one will be shown
+    pub keys: Vec<String>,
+    /// The problem message
+    pub message: String,
+}
+
+impl ConfigProblem {
+    pub fn new(keys: &[&str], message: impl Into<String>) -> Self {
+        Self {
+            keys: keys.iter().map(|key| (*key).to_owned()).collect(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A config file source, to show the snippets of its keys
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// Return the caret annotated snippet of the `start` line in the `text` of `path`,
+/// the carets are under the `start..end` span (to the line end)
+fn snippet(path: &Path, text: &str, (start, end): (usize, usize)) -> String {
+    let line_start = text[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_number = text[..line_start].matches('\n').count() + 1;
+    let line = text[line_start..].lines().next().unwrap_or_default();
+    let prefix = &text[line_start..start];
+    let carets_count = text[start..end.clamp(start, line_start + line.len())]
+        .chars()
+        .count()
+        .max(1);
+    // Keep the tabs of the line, so the carets will be under the value
+    let padding: String = prefix
+        .chars()
+        .map(|chr| if chr == '\t' { '\t' } else { ' ' })
+        .collect();
+    let gutter = " ".repeat(line_number.to_string().len());
+    format!(
+        "{gutter}{arrow} {}:{line_number}:{}\n{gutter} {bar}\n{line_number} {bar} {line}\n{gutter} {bar} {padding}{}",
+        path.display(),
+        prefix.chars().count() + 1,
+        "^".repeat(carets_count).red(),
+        arrow = "-->".blue(),
+        bar = "|".blue(),
+    )
+}
+
+/// Return a parse error of the config file, with the snippet of the error position
+pub fn parse_error(path: &Path, text: &str, err: &SpannedError) -> ApcError {
+    let message = format!("Cannot parse config file '{}' {}", path.display(), err.code);
+    // The line and the column (in bytes) start from 1, zero if there is no position
+    let offset = text
+        .split_inclusive('\n')
+        .take(err.position.line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>()
+        + err.position.col.saturating_sub(1);
+    if err.position.line == 0 || offset > text.len() || !text.is_char_boundary(offset) {
+        return ApcError::ParseRon(message);
+    }
+    ApcError::ParseRon(format!(
+        "{message}\n{}",
+        snippet(path, text, (offset, offset))
+    ))
+}
+
+/// A RON tokens scanner, to find the positions of the config values
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// Return the current byte
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.idx).copied()
+    }
+
+    /// Skip the whitespaces and the comments
+    fn skip_trivia(&mut self) {
+        loop {
+            match (self.peek(), self.bytes.get(self.idx + 1)) {
+                (Some(byte), _) if byte.is_ascii_whitespace() => self.idx += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while self.peek().is_some_and(|byte| byte != b'\n') {
+                        self.idx += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    // The block comments can be nested
+                    let mut depth = 0;
+                    while self.idx < self.bytes.len() {
+                        match &self.bytes[self.idx..] {
+                            [b'/', b'*', ..] => {
+                                depth += 1;
+                                self.idx += 2;
+                            }
+                            [b'*', b'/', ..] => {
+                                depth -= 1;
+                                self.idx += 2;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => self.idx += 1,
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Skip a quoted string or char, return its content
+    fn quoted(&mut self, quote: u8) -> &'a str {
+        self.idx += 1;
+        let start = self.idx;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'\\' => self.idx += 2,
+                byte if byte == quote => break,
+                _ => self.idx += 1,
+            }
+        }
+        let end = self.idx.min(self.bytes.len());
+        self.idx = end + 1;
+        std::str::from_utf8(&self.bytes[start..end]).unwrap_or_default()
+    }
+
+    /// Skip a raw string (`r#"..."#`)
+    fn skip_raw_string(&mut self) {
+        self.idx += 1;
+        let hashes = self.bytes[self.idx..]
+            .iter()
+            .take_while(|byte| **byte == b'#')
+            .count();
+        self.idx += hashes + 1;
+        while self.idx < self.bytes.len() {
+            if self.bytes[self.idx] == b'"'
+                && self.bytes[self.idx + 1..]
+                    .iter()
+                    .take(hashes)
+                    .filter(|byte| **byte == b'#')
+                    .count()
+                    == hashes
+            {
+                self.idx += hashes + 1;
+                return;
+            }
+            self.idx += 1;
+        }
+    }
+
+    /// Skip an identifier, return it
+    fn identifier(&mut self) -> &'a str {
+        let start = self.idx;
+        while self
+            .peek()
+            .is_some_and(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+        {
+            self.idx += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.idx]).unwrap_or_default()
+    }
+
+    /// Return true if a raw string starts at the current byte
+    fn is_raw_string(&self) -> bool {
+        self.peek() == Some(b'r')
+            && self.bytes[self.idx + 1..]
+                .iter()
+                .find(|byte| **byte != b'#')
+                .is_some_and(|byte| *byte == b'"')
+    }
+}
+
+/// Return the span of the values in the RON `text`, by their keys path (`a.b.c`).
+/// The span of a struct, a list or a map is its opening bracket.
+/// The map keys (e.g. the profiles names) are a part of the path like the struct fields
+fn values_positions(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        idx: 0,
+    };
+    // The opened brackets, with the key of their value
+    let mut brackets: Vec<(u8, Option<String>)> = Vec::new();
+    // The key of the next value
+    let mut key: Option<String> = None;
+    // The key of the named struct value (`Name(..)`), its bracket will have it
+    let mut struct_key: Option<String> = None;
+    let mut positions = HashMap::new();
+    let path = |brackets: &[(u8, Option<String>)], key: &str| {
+        brackets
+            .iter()
+            .filter_map(|(_, key)| key.as_deref())
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+
+    loop {
+        scanner.skip_trivia();
+        let start = scanner.idx;
+        let Some(byte) = scanner.peek() else {
+            break;
+        };
+        let token = match byte {
+            b'(' | b'[' | b'{' => {
+                if let Some(key) = &key {
+                    positions.insert(path(&brackets, key), (start, start + 1));
+                }
+                brackets.push((byte, key.take().or(struct_key.take())));
+                scanner.idx += 1;
+                continue;
+            }
+            b')' | b']' | b'}' => {
+                brackets.pop();
+                key = None;
+                scanner.idx += 1;
+                continue;
+            }
+            b',' => {
+                scanner.idx += 1;
+                continue;
+            }
+            b'"' => Some(scanner.quoted(b'"')),
+            b'\'' => {
+                scanner.quoted(b'\'');
+                None
+            }
+            _ if scanner.is_raw_string() => {
+                scanner.skip_raw_string();
+                None
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => Some(scanner.identifier()),
+            _ => {
+                // Numbers and the other tokens, one byte at least
+                scanner.idx += 1;
+                while scanner.peek().is_some_and(|byte| {
+                    !byte.is_ascii_whitespace() && !b",:()[]{}/\"".contains(&byte)
+                }) {
+                    scanner.idx += 1;
+                }
+                None
+            }
+        };
+        let is_identifier = byte != b'"';
+        let end = scanner.idx;
+        scanner.skip_trivia();
+        match (token, scanner.peek()) {
+            // A struct field, or a string key of a map
+            (Some(token), Some(b':'))
+                if is_identifier || brackets.last().is_some_and(|(byte, _)| *byte == b'{') =>
+            {
+                key = Some(token.to_owned());
+                scanner.idx += 1;
+            }
+            (_, next) => {
+                if let Some(key) = key.take() {
+                    positions.insert(path(&brackets, &key), (start, end));
+                    if is_identifier && next == Some(b'(') {
+                        struct_key = Some(key);
+                    }
+                }
+            }
+        }
+    }
+    positions
+}
+
+/// Return the problems of the config with the snippets of their keys, from the files of their `origins`.
+/// The keys that are set by the overrides have a note with their origin instead
+pub fn render(
+    problems: &[ConfigProblem],
+    sources: &[Source],
+    origins: &BTreeMap<String, Origin>,
+) -> String {
+    let positions: Vec<_> = sources
+        .iter()
+        .map(|source| (source, values_positions(&source.text)))
+        .collect();
+    let find = |path: &Path, key: &str| {
+        positions
+            .iter()
+            .rev()
+            .find(|(source, _)| source.path == path)
+            .and_then(|(source, positions)| {
+                positions
+                    .get(key)
+                    .map(|span| snippet(&source.path, &source.text, *span))
+            })
+    };
+    let note = "= note:".blue();
+    let rendered: Vec<String> = problems
+        .iter()
+        .map(|problem| {
+            let snippets: Vec<String> = problem
+                .keys
+                .iter()
+                .filter_map(|key| match origins.get(key)? {
+                    Origin::File(path) => find(path, key),
+                    Origin::Profile { name, path } => find(path, &format!("profiles.{name}.{key}")),
+                    origin => Some(format!("   {note} `{key}` is set by {origin}")),
+                })
+                .collect();
+            if snippets.is_empty() {
+                format!(
+                    "{}\n   {note} it's the default value, it's not written in the config files",
+                    problem.message,
+                )
+            } else {
+                format!("{}\n{}", problem.message, snippets.join("\n"))
+            }
+        })
+        .collect();
+    format!(
+        "Found {} problems in the config\n\n{}\n\n{CONFIG_ISSUE}",
+        problems.len(),
+        rendered.join("\n\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the text of the key value span
+    fn value<'a>(text: &'a str, key: &str) -> &'a str {
+        let (start, end) = values_positions(text)[key];
+        &text[start..end]
+    }
+
+    #[test]
+    fn values_of_nested_structs() {
+        let text =
+            r#"(posts_path: "/blog/", header_image_settings: (quality: 75, variants: [480, 960]))"#;
+        assert_eq!(value(text, "posts_path"), r#""/blog/""#);
+        assert_eq!(value(text, "header_image_settings"), "(");
+        assert_eq!(value(text, "header_image_settings.quality"), "75");
+        assert_eq!(value(text, "header_image_settings.variants"), "[");
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let text = "(\n  // a: 1,\n  /* b: 2 /* c: 3 */ d: 4 */ e: 5,\n)";
+        let positions = values_positions(text);
+        for key in ["a", "b", "c", "d"] {
+            assert!(!positions.contains_key(key), "`{key}` is in a comment");
+        }
+        assert_eq!(value(text, "e"), "5");
+    }
+
+    #[test]
+    fn strings_content_is_skipped() {
+        let text = r##"(a: r#"b: "(c", d: 1"#, e: "f: (g\" h: 2", i: ',', j: 3)"##;
+        let positions = values_positions(text);
+        for key in ["b", "d", "f", "h"] {
+            assert!(!positions.contains_key(key), "`{key}` is in a string");
+        }
+        assert_eq!(value(text, "a"), r##"r#"b: "(c", d: 1"#"##);
+        assert_eq!(value(text, "e"), r#""f: (g\" h: 2""#);
+        assert_eq!(value(text, "i"), "','");
+        assert_eq!(value(text, "j"), "3");
+    }
+
+    #[test]
+    fn named_structs_fields() {
+        let text =
+            "(header_card_settings: HeaderCardSettings(width: 800), trash_path: Some(\"/t\"))";
+        assert_eq!(value(text, "header_card_settings"), "HeaderCardSettings");
+        assert_eq!(value(text, "header_card_settings.width"), "800");
+        assert_eq!(value(text, "trash_path"), "Some");
+        assert!(!values_positions(text).contains_key("trash_path.Some"));
+    }
+
+    #[test]
+    fn map_keys_are_in_the_path() {
+        let text = r#"(profiles: {"news": (posts_path: "/news/"), "docs": ()})"#;
+        assert_eq!(value(text, "profiles"), "{");
+        assert_eq!(value(text, "profiles.news"), "(");
+        assert_eq!(value(text, "profiles.news.posts_path"), r#""/news/""#);
+        assert_eq!(value(text, "profiles.docs"), "(");
+    }
+
+    /// Return the snippet without the colors
+    fn plain_snippet(text: &str, span: (usize, usize)) -> String {
+        colored::control::set_override(false);
+        snippet(Path::new("config.ron"), text, span)
+    }
+
+    #[test]
+    fn snippet_carets_under_the_value() {
+        let text = "(\n  quality: 750,\n)";
+        let start = text.find("750").unwrap();
+        assert_eq!(
+            plain_snippet(text, (start, start + 3)),
+            " --> config.ron:2:12\n  |\n2 |   quality: 750,\n  |            ^^^"
+        );
+    }
+
+    #[test]
+    fn snippet_keeps_the_tabs() {
+        let text = "(\n\tquality: 750,\n)";
+        let start = text.find("750").unwrap();
+        assert_eq!(
+            plain_snippet(text, (start, start + 3)),
+            " --> config.ron:2:11\n  |\n2 | \tquality: 750,\n  | \t         ^^^"
+        );
+    }
+
+    #[test]
+    fn snippet_counts_the_characters() {
+        let text = "(title: \"عنوان\", date_format: \"%Y\")";
+        let start = text.find("\"%Y\"").unwrap();
+        assert_eq!(
+            plain_snippet(text, (start, start + 4)),
+            " --> config.ron:1:31\n  |\n1 | (title: \"عنوان\", date_format: \"%Y\")\n  |                               ^^^^"
+        );
+        let start = text.find("\"ع").unwrap();
+        let end = text.find(", date").unwrap();
+        assert_eq!(
+            plain_snippet(text, (start, end)).lines().last(),
+            Some("  |         ^^^^^^^")
+        );
+    }
+
+    #[test]
+    fn snippet_carets_stop_at_the_line_end() {
+        let text = "(\n  profiles: {\n  },\n)";
+        let start = text.find('{').unwrap();
+        assert_eq!(
+            plain_snippet(text, (start, text.len())).lines().last(),
+            Some("  |             ^")
+        );
+    }
+
+    #[test]
+    fn snippet_of_the_parse_error() {
+        let text = "(\n  quality: ],\n)";
+        let err = ron::Options::default()
+            .from_str::<ron::Value>(text)
+            .unwrap_err();
+        colored::control::set_override(false);
+        let ApcError::ParseRon(message) = parse_error(Path::new("config.ron"), text, &err) else {
+            panic!("It's not a parse error");
+        };
+        assert!(
+            message.ends_with("2 |   quality: ],\n  |            ^"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn snippet_of_the_parse_error_after_multibyte_text() {
+        let text = "(title: \"عنوان\" date_format: \"%Y\")";
+        let err = ron::Options::default()
+            .from_str::<ron::Value>(text)
+            .unwrap_err();
+        colored::control::set_override(false);
+        let ApcError::ParseRon(message) = parse_error(Path::new("config.ron"), text, &err) else {
+            panic!("It's not a parse error");
+        };
+        // The caret is under `date_format`, the column is in characters
+        assert!(
+            message.ends_with(
+                ":1:17\n  |\n1 | (title: \"عنوان\" date_format: \"%Y\")\n  |                 ^"
+            ),
+            "{message}"
+        );
+    }
+}
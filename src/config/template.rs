@@ -0,0 +1,151 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{
+    Config, CreatePostSettings, DeletePostSettings, HeaderCardSettings, HeaderImageRules,
+    HeaderImageSettings, ModifyPostSettings, SelectAction,
+};
+
+/// The documentation of the config sections keys, by the section path (empty for the top-level keys).
+/// The docs are the fields doc comments, see `config_section!`. The profiles keys are not in the template
+const SECTIONS_DOCS: &[(&str, &[(&str, &str)])] = &[
+    ("", Config::DOCS),
+    ("header_image_settings", HeaderImageSettings::DOCS),
+    ("header_image_rules", HeaderImageRules::DOCS),
+    ("header_card_settings", HeaderCardSettings::DOCS),
+    ("select_action", SelectAction::DOCS),
+    ("create_post_settings", CreatePostSettings::DOCS),
+    ("modify_post_settings", ModifyPostSettings::DOCS),
+    ("delete_post_settings", DeletePostSettings::DOCS),
+];
+
+/// Return the documentation of the key path (`a.b.c`), empty if it's not documented
+fn key_doc(path: &str) -> &'static str {
+    let (section, key) = path.rsplit_once('.').unwrap_or(("", path));
+    SECTIONS_DOCS
+        .iter()
+        .filter(|(name, _)| *name == section)
+        .flat_map(|(_, docs)| docs.iter())
+        .find(|(name, _)| *name == key)
+        .map_or("", |(_, doc)| doc.trim())
+}
+
+/// Return the config file with every key and its default value, with its documentation above it.
+/// The keys will be commented out with `comment_values`, so the file will not set any key
+pub fn commented_config(comment_values: bool) -> String {
+    let config = Config::default();
+    let defaults = ron::ser::to_string_pretty(&config, ron::ser::PrettyConfig::new())
+        .expect("The default config is serializable");
+    let mut lines = vec![
+        if comment_values {
+            "// Alepc configuration file, uncomment a key to change its default value".to_owned()
+        } else {
+            "// Alepc configuration file, the values are the defaults".to_owned()
+        },
+        format!("// See <{}#configuration>", config.repository_url),
+    ];
+    let last_line = defaults.lines().count().saturating_sub(1);
+    // The keys of the opened sections
+    let mut sections: Vec<&str> = Vec::new();
+    for (idx, line) in defaults.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some((key, value)) = trimmed.split_once(": ") {
+            let path = sections
+                .iter()
+                .chain(std::iter::once(&key))
+                .copied()
+                .collect::<Vec<_>>()
+                .join(".");
+            let doc = key_doc(&path);
+            if !doc.is_empty() {
+                lines.push(format!("{indent}// {doc}"));
+            }
+            if value == "(" {
+                sections.push(key);
+            }
+        } else if trimmed.starts_with(')') {
+            sections.pop();
+        }
+        // The parentheses of the file itself are kept
+        if comment_values && idx != 0 && idx != last_line {
+            lines.push(format!("{indent}// {trimmed}"));
+        } else {
+            lines.push(line.to_owned());
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn every_key_is_documented() {
+        let template = commented_config(false);
+        let keys = template
+            .lines()
+            .map(str::trim_start)
+            .filter(|line| !line.starts_with("//") && line.contains(": "))
+            .count();
+        let docs = template
+            .lines()
+            .filter(|line| line.trim_start().starts_with("//"))
+            .count();
+        // The two header lines
+        assert_eq!(docs - 2, keys);
+    }
+
+    /// Push the dotted paths of the value keys to `paths`, the maps are sections if they are not empty
+    fn keys_paths(prefix: &str, value: &serde_json::Value, paths: &mut BTreeSet<String>) {
+        for (key, value) in value.as_object().into_iter().flatten() {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            keys_paths(&path, value, paths);
+            paths.insert(path);
+        }
+    }
+
+    #[test]
+    fn documented_keys_are_the_config_keys() {
+        let config = serde_json::to_value(Config::default()).unwrap();
+        let mut keys = BTreeSet::new();
+        keys_paths("", &config, &mut keys);
+        let documented: BTreeSet<String> = SECTIONS_DOCS
+            .iter()
+            .flat_map(|(section, docs)| {
+                docs.iter().map(move |(key, _)| {
+                    if section.is_empty() {
+                        (*key).to_owned()
+                    } else {
+                        format!("{section}.{key}")
+                    }
+                })
+            })
+            .collect();
+        assert_eq!(documented, keys);
+        for key in &keys {
+            assert!(!key_doc(key).is_empty(), "`{key}` is not documented");
+        }
+    }
+}
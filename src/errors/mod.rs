@@ -18,7 +18,6 @@
 mod statuses;
 
 use colored::Colorize;
-use ron::error::{Position, SpannedError};
 use strum::IntoStaticStr;
 use thiserror::Error;
 
@@ -26,11 +25,8 @@ pub use statuses::Statuses;
 /// Alepc errors
 #[derive(IntoStaticStr, Error, Debug, Clone)]
 pub enum ApcError {
-    #[error("Cannot parse config file '{code}' in {position}")]
-    ParseRon {
-        code: SpannedError,
-        position: Position,
-    },
+    #[error("{0}")]
+    ParseRon(String),
     #[error("{0}")]
     Validation(String),
     #[error("{0}")]
@@ -58,6 +54,23 @@ impl ApcError {
         (!matches!(self, Self::Requestty(_)))
             .then(|| eprintln!("{}: {}", format!("{}Error", self.name()).red(), self));
     }
+
+    /// Print error message with its source (e.g. the post path),
+    /// the source will not be repeated if the message already starts with it
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn print_in(&self, source: &std::path::Path) {
+        let message = self.to_string();
+        let source = format!("'{}", source.display());
+        if message.starts_with(&source) {
+            self.print();
+        } else {
+            eprintln!(
+                "{}: {source}' {message}",
+                format!("{}Error", self.name()).red(),
+            );
+        }
+    }
 }
 
 pub type ApcResult<T> = Result<T, ApcError>;
@@ -30,11 +30,7 @@ impl Termination for ApcError {
             // permission denied
             Self::FileSystem(_) => 77,
             // configuration error
-            Self::Validation(_)
-            | Self::ParseRon {
-                code: _,
-                position: _,
-            } => 78,
+            Self::Validation(_) | Self::ParseRon(_) => 78,
             _ => 1,
         }
         .into()
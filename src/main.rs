@@ -23,22 +23,46 @@ extern crate lazy_static;
 extern crate pretty_env_logger;
 
 mod app;
+mod cli;
+mod commands;
 mod config;
 mod errors;
 mod utils;
 
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use errors::{ApcError, Statuses};
 use lazy_static::lazy_static;
 use std::env::var;
 
 lazy_static! {
-    static ref CONFIG: Result<config::Config, ApcError> = config::get_config();
+    static ref CLI: cli::Cli = cli::Cli::parse();
+    static ref CONFIG: Result<config::Config, ApcError> = config::get_config(
+        CLI.config.as_deref(),
+        CLI.profile.as_deref(),
+        CLI.uses_wizard(),
+        &CLI.overrides,
+    );
 }
 
 fn main() -> Statuses<ApcError> {
     var("RUST_LOG").is_ok().then(pretty_env_logger::init);
+    // Parse the arguments before loading the config, so `--help` always works
+    lazy_static::initialize(&CLI);
+    // The commands have their own `--dry-run`, the global one is for the wizard
+    if CLI.dry_run && CLI.command.is_some() {
+        cli::Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "`--dry-run` before the command is for the wizard, use `<COMMAND> --dry-run`",
+            )
+            .exit();
+    }
+    // The config commands don't need a valid config
+    if let Some(cli::Command::Config(command)) = &CLI.command {
+        return commands::config::config(&CLI, command).into();
+    }
     match CONFIG.as_ref() {
-        Ok(alepc_config) => app::run(alepc_config).into(),
+        Ok(alepc_config) => app::run(alepc_config, &CLI).into(),
         Err(err) => Statuses::Failure(err.clone()),
     }
 }
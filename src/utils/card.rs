@@ -0,0 +1,259 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::HeaderCardSettings;
+use crate::errors::{ApcError, ApcResult};
+use base64::Engine;
+use image::{DynamicImage, RgbaImage};
+use resvg::usvg::fontdb;
+use resvg::{tiny_skia, usvg};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+/// The default card template, designed for 1200x630 and scaled to the card size
+const DEFAULT_TEMPLATE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="{{width}}" height="{{height}}" viewBox="0 0 1200 630" preserveAspectRatio="none">
+  <rect width="1200" height="630" fill="{{background}}"/>
+  <rect width="24" height="630" fill="{{accent}}"/>
+  {{logo_image}}
+  <text font-family="{{font_family}}" font-size="64" font-weight="bold" fill="{{foreground}}">{{title_lines}}</text>
+  <text x="96" y="560" font-family="{{font_family}}" font-size="32" fill="{{accent}}">{{tags}}</text>
+</svg>
+"#;
+
+/// Maximum characters of a title line in the default template
+const LINE_CHARS: usize = 24;
+/// Maximum title lines in the default template
+const MAX_LINES: usize = 3;
+
+/// Escape the text to be written in the SVG
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Wrap the title words in lines, the remaining words after the last line are replaced with `…`
+fn wrap(title: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in title.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + word.chars().count() < LINE_CHARS => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    if lines.len() > MAX_LINES {
+        lines.truncate(MAX_LINES);
+        lines[MAX_LINES - 1].push('…');
+    }
+    lines
+}
+
+/// Return the title lines as `tspan` elements, centered vertically in the default template
+fn title_lines(title: &str) -> String {
+    let lines = wrap(title);
+    let first_line = 322 - (lines.len().saturating_sub(1) * 40);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                r#"<tspan x="96" y="{}">{}</tspan>"#,
+                first_line + index * 80,
+                escape(line)
+            )
+        })
+        .collect()
+}
+
+/// Return the logo as data URI, to keep the card self-contained
+/// ### Errors
+/// * If the logo cannot be read
+fn logo_uri(path: &str) -> ApcResult<String> {
+    let bytes = fs::read(path).map_err(|err| ApcError::FileSystem(format!("`{path}` {err}")))?;
+    let mime = match super::path_extension(path).to_ascii_lowercase().as_str() {
+        "svg" => "image/svg+xml",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "image/png",
+    };
+    Ok(format!(
+        "data:{mime};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+/// Render the card SVG from the template, by replacing its placeholders in one pass:
+/// `{{width}}`, `{{height}}`, `{{background}}`, `{{foreground}}`, `{{accent}}`, `{{font_family}}`,
+/// `{{title}}`, `{{title_lines}}`, `{{tags}}`, `{{logo}}` and `{{logo_image}}`
+/// ### Errors
+/// * If the template or the logo cannot be read
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn render(settings: &HeaderCardSettings, title: &str, tags: &[String]) -> ApcResult<String> {
+    let template = match &settings.template_path {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| ApcError::FileSystem(format!("`{path}` {err}")))?,
+        None => DEFAULT_TEMPLATE.to_owned(),
+    };
+    let logo = settings
+        .logo_path
+        .as_deref()
+        .map(logo_uri)
+        .transpose()?
+        .unwrap_or_default();
+    let logo_image = if logo.is_empty() {
+        String::new()
+    } else {
+        format!(r#"<image x="1040" y="48" width="112" height="112" href="{logo}"/>"#)
+    };
+    let tags = tags
+        .iter()
+        .map(|tag| format!("#{tag}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let values: HashMap<&str, String> = [
+        ("width", settings.width.to_string()),
+        ("height", settings.height.to_string()),
+        ("background", escape(&settings.background)),
+        ("foreground", escape(&settings.foreground)),
+        ("accent", escape(&settings.accent)),
+        ("font_family", escape(&settings.font_family)),
+        ("title", escape(title)),
+        ("title_lines", title_lines(title)),
+        ("tags", escape(&tags)),
+        ("logo", logo),
+        ("logo_image", logo_image),
+    ]
+    .into_iter()
+    .collect();
+    Ok(substitute(&template, &values))
+}
+
+/// Replace the `{{key}}` placeholders of the template with their values in one pass,
+/// so the placeholders in the values (e.g. the title) are not replaced.
+/// The unknown placeholders are kept as they are
+fn substitute(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let token = &rest[start..];
+        match token[2..]
+            .find("}}")
+            .and_then(|end| Some((end, values.get(&token[2..2 + end])?)))
+        {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &token[end + 4..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = &token[2..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Return the fonts database, with the system fonts and `font_path`.
+/// The `font_path` font will be the `sans-serif` font, otherwise the first system sans font if
+/// the default one (Arial) is missing
+/// ### Errors
+/// * If the font cannot be loaded
+fn fonts(settings: &HeaderCardSettings) -> ApcResult<fontdb::Database> {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    let family_of = |fonts: &fontdb::Database, id| {
+        fonts
+            .face(id)
+            .and_then(|face| face.families.first())
+            .map(|(family, _)| family.clone())
+    };
+    let sans_serif = if let Some(font_path) = &settings.font_path {
+        let data = fs::read(font_path)
+            .map_err(|err| ApcError::FileSystem(format!("`{font_path}` {err}")))?;
+        let id = fonts
+            .load_font_source(fontdb::Source::Binary(Arc::new(data)))
+            .first()
+            .copied()
+            .ok_or_else(|| ApcError::Validation(format!("`{font_path}` is not a font")))?;
+        family_of(&fonts, id)
+    } else if fonts
+        .query(&fontdb::Query {
+            families: &[fontdb::Family::SansSerif],
+            ..Default::default()
+        })
+        .is_none()
+    {
+        fonts
+            .faces()
+            .find(|face| {
+                face.families
+                    .iter()
+                    .any(|(family, _)| family.ends_with(" Sans"))
+            })
+            .or_else(|| fonts.faces().next())
+            .map(|face| face.id)
+            .and_then(|id| family_of(&fonts, id))
+    } else {
+        None
+    };
+    if let Some(family) = sans_serif {
+        fonts.set_sans_serif_family(family);
+    }
+    Ok(fonts)
+}
+
+/// Rasterize the card SVG in the card size, with the system fonts and `font_path`
+/// ### Errors
+/// * If the SVG is invalid, or the font cannot be loaded
+#[logfn_inputs(Info)]
+pub fn rasterize(settings: &HeaderCardSettings, svg: &str) -> ApcResult<DynamicImage> {
+    let options = usvg::Options {
+        fontdb: Arc::new(fonts(settings)?),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)
+        .map_err(|err| ApcError::Validation(format!("Invalid header card SVG: {err}")))?;
+    let mut pixmap = tiny_skia::Pixmap::new(settings.width, settings.height)
+        .ok_or_else(|| ApcError::Other("Cannot create the header card pixmap".to_owned()))?;
+    let transform = tiny_skia::Transform::from_scale(
+        settings.width as f32 / tree.size().width(),
+        settings.height as f32 / tree.size().height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    // The pixmap is premultiplied by the alpha
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(settings.width, settings.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| ApcError::Other("Cannot read the header card pixels".to_owned()))
+}
@@ -0,0 +1,464 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::errors::{ApcError, ApcResult};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// The frontmatter delimiter
+pub const DELIMITER: &str = "---";
+
+/// Split the post content to `(frontmatter, body)` by the `---` delimiters.
+/// The frontmatter is the YAML between the first line and the next `---` line,
+/// and the body is everything after the closing delimiter line.
+///
+/// Return `None` if the content doesn't start with a frontmatter.
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let first_line_end = content.find('\n')?;
+    if content[..first_line_end].trim_end() != DELIMITER {
+        return None;
+    }
+    let frontmatter_start = first_line_end + 1;
+    let mut line_start = frontmatter_start;
+    while line_start <= content.len() {
+        let line_end = content[line_start..]
+            .find('\n')
+            .map_or(content.len(), |idx| line_start + idx);
+        if content[line_start..line_end].trim_end() == DELIMITER {
+            let body_start = (line_end + 1).min(content.len());
+            return Some((
+                &content[frontmatter_start..line_start],
+                &content[body_start..],
+            ));
+        }
+        line_start = line_end + 1;
+    }
+    None
+}
+
+/// Return true if `line` is the first line of the top-level `key` entry
+fn is_key_line(line: &str, key: &str) -> bool {
+    let line = line.trim_start_matches(['"', '\'']);
+    line.strip_prefix(key).is_some_and(|rest| {
+        rest.trim_start_matches(['"', '\''])
+            .trim_start()
+            .starts_with(':')
+    })
+}
+
+/// Return true if `line` is a continuation of the previous top-level entry,
+/// e.g. a nested value, a multi-line string or a list item
+fn is_continuation_line(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line == "-" || line.starts_with("- ")
+}
+
+/// Return the lines range of the top-level `key` entry
+fn entry_range(lines: &[&str], key: &str) -> Option<(usize, usize)> {
+    let start = lines
+        .iter()
+        .position(|line| !is_continuation_line(line) && is_key_line(line, key))?;
+    // The entry ends at the last continuation line, the empty lines after it are not part of it
+    let mut end = start + 1;
+    for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+        if is_continuation_line(line) {
+            end = idx + 1;
+        } else if !line.trim().is_empty() {
+            break;
+        }
+    }
+    Some((start, end))
+}
+
+/// Set the value of top-level `key` in the frontmatter source (without the delimiters).
+/// The entry will be replaced in its place, and the other lines (unknown keys, comments, order)
+/// will stay as they are. If the key doesn't exist, it will be appended to the end.
+///
+/// `value` should be a rendered YAML value, see [`render`]
+pub fn set(source: &str, key: &str, value: &str) -> String {
+    let entry = format!("{key}: {value}\n");
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let Some((start, end)) = entry_range(&lines, key) else {
+        let separator = if source.is_empty() || source.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        return format!("{source}{separator}{entry}");
+    };
+    format!(
+        "{}{entry}{}",
+        lines[..start].concat(),
+        lines[end..].concat()
+    )
+}
+
+/// Remove the top-level `key` entry from the frontmatter source (without the delimiters),
+/// the other lines will stay as they are
+pub fn remove(source: &str, key: &str) -> String {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    match entry_range(&lines, key) {
+        Some((start, end)) => format!("{}{}", lines[..start].concat(), lines[end..].concat()),
+        None => source.to_owned(),
+    }
+}
+
+/// Quote a string as YAML double-quoted scalar, so it will be read back as it is.
+/// The printable characters (including non-ASCII) will be kept, and the others will be escaped.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for chr in value.chars() {
+        match chr {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\0' => quoted.push_str("\\0"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            // YAML line breaks
+            '\u{85}' => quoted.push_str("\\N"),
+            '\u{2028}' => quoted.push_str("\\L"),
+            '\u{2029}' => quoted.push_str("\\P"),
+            // Byte order mark, and the other non-printable characters
+            '\u{feff}' | '\u{fffe}' | '\u{ffff}' => {
+                quoted.push_str(&format!("\\u{:04X}", chr as u32))
+            }
+            chr if chr.is_control() => quoted.push_str(&format!("\\x{:02X}", chr as u32)),
+            chr => quoted.push(chr),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Render a YAML value to be written in the frontmatter.
+/// The strings are double-quoted and escaped, and the lists are in flow style (`["a", "b"]`)
+pub fn render(value: &Value) -> String {
+    match value {
+        Value::String(value) => quote(value),
+        Value::Sequence(values) => format!(
+            "[{}]",
+            values.iter().map(render).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Bool(value) => value.to_string(),
+        _ => unreachable!("The post properties are strings, booleans and lists of strings"),
+    }
+}
+
+/// Parsed post frontmatter
+#[derive(Debug)]
+pub struct Frontmatter<'a> {
+    /// Path of the post, for the errors
+    path: &'a Path,
+    /// The frontmatter source (without the delimiters)
+    source: &'a str,
+    /// The frontmatter keys and values, in the file order
+    mapping: Mapping,
+}
+
+impl<'a> Frontmatter<'a> {
+    /// Parse the frontmatter source (without the delimiters) of `path`
+    /// ## Errors:
+    /// - If the frontmatter is invalid YAML, or it's not a key value mapping
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn parse(source: &'a str, path: &'a Path) -> ApcResult<Self> {
+        // The leading new line is the first delimiter line, so the YAML lines are the file lines
+        let mapping = match serde_yaml::from_str::<Value>(&format!("\n{source}")) {
+            Ok(Value::Mapping(mapping)) => mapping,
+            // Empty frontmatter
+            Ok(Value::Null) => Mapping::new(),
+            Ok(_) => {
+                return Err(ApcError::PostProperties(format!(
+                    "'{}:2' the post properties should be `key: value`",
+                    path.display()
+                )))
+            }
+            Err(err) => {
+                let line = err.location().map_or(2, |location| location.line());
+                return Err(ApcError::PostProperties(format!(
+                    "'{}:{line}' invalid post properties: {err}",
+                    path.display()
+                )));
+            }
+        };
+        Ok(Self {
+            path,
+            source,
+            mapping,
+        })
+    }
+
+    /// Return the line number of `key` in the post file,
+    /// or the first line of the frontmatter if the key doesn't exist
+    fn key_line(&self, key: &str) -> usize {
+        self.source
+            .lines()
+            .position(|line| is_key_line(line, key))
+            .map_or(1, |idx| idx + 2)
+    }
+
+    /// Return a post properties error of `key`, with its line
+    pub fn error(&self, key: &str, message: &str) -> ApcError {
+        ApcError::PostProperties(format!(
+            "'{}:{}' `{key}` {message}",
+            self.path.display(),
+            self.key_line(key)
+        ))
+    }
+
+    /// Return the frontmatter keys and values
+    pub fn mapping(&self) -> &Mapping {
+        &self.mapping
+    }
+
+    /// Return the value of `key`
+    fn value(&self, key: &str) -> ApcResult<&Value> {
+        self.mapping
+            .get(key)
+            .ok_or_else(|| self.error(key, "property is missing, and it's should exist"))
+    }
+
+    /// Return the string value of `key`
+    pub fn string(&self, key: &str) -> ApcResult<String> {
+        match self.value(key)? {
+            Value::String(value) => Ok(value.clone()),
+            _ => Err(self.error(key, "should be a string")),
+        }
+    }
+
+    /// Return the boolean value of `key`
+    pub fn bool(&self, key: &str) -> ApcResult<bool> {
+        self.value(key)?
+            .as_bool()
+            .ok_or_else(|| self.error(key, "should be a boolean (`true` or `false`)"))
+    }
+
+    /// Return the strings list value of `key`
+    pub fn strings(&self, key: &str) -> ApcResult<Vec<String>> {
+        self.value(key)?
+            .as_sequence()
+            .ok_or_else(|| self.error(key, "should be a list of strings"))?
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(ToOwned::to_owned)
+                    .ok_or_else(|| self.error(key, "should be a list of strings"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the frontmatter of the source, the tests path is not a file
+    fn parse(source: &str) -> Frontmatter<'_> {
+        Frontmatter::parse(source, Path::new("post.md")).expect("The frontmatter is valid")
+    }
+
+    const TEXTS: &[&str] = &[
+        "He said \"hi\"",
+        r"C:\path\to\file \n",
+        "first line\nsecond line\r\nthird line",
+        "\0 \x01 \x07 \x1b \x7f \u{80} \u{9f} \t",
+        "line\u{2028}separator\u{2029}paragraph\u{85}next",
+        "\u{feff}BOM \u{fffe} \u{ffff}",
+        "عنوان المقالة: مقدمة # ليس تعليقا",
+        "  - not a list, [not] {a: map} & *alias !tag %",
+        "null",
+        "true",
+        "",
+    ];
+
+    #[test]
+    fn strings_round_trip() {
+        for text in TEXTS {
+            let source = set("", "title", &render(&Value::from(*text)));
+            assert_eq!(
+                parse(&source).string("title").as_deref().ok(),
+                Some(*text),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let tags: Vec<String> = TEXTS.iter().map(|text| (*text).to_owned()).collect();
+        let source = set("", "tags", &render(&Value::from(tags.clone())));
+        assert_eq!(parse(&source).strings("tags").ok(), Some(tags));
+    }
+
+    const SOURCE: &str = "\
+# The post title
+title: Hello
+description: |
+  First line
+
+  Second line
+# The post tags
+tags:
+  - rust
+  - yaml
+
+date: 2024-01-01
+dateModified: 2024-02-01
+draft: true # not yet
+";
+
+    #[test]
+    fn set_replaces_a_block_scalar() {
+        assert_eq!(
+            set(SOURCE, "description", "\"New\""),
+            SOURCE.replace("|\n  First line\n\n  Second line\n", "\"New\"\n")
+        );
+    }
+
+    #[test]
+    fn set_replaces_a_block_list() {
+        assert_eq!(
+            set(SOURCE, "tags", "[\"a\"]"),
+            SOURCE.replace("\n  - rust\n  - yaml\n", " [\"a\"]\n")
+        );
+    }
+
+    #[test]
+    fn set_keeps_the_comments() {
+        assert_eq!(
+            set(SOURCE, "title", "\"Bye\""),
+            SOURCE.replace("title: Hello", "title: \"Bye\"")
+        );
+        assert_eq!(
+            set(SOURCE, "draft", "false"),
+            SOURCE.replace("draft: true # not yet", "draft: false")
+        );
+    }
+
+    #[test]
+    fn set_matches_the_whole_key() {
+        assert_eq!(
+            set(SOURCE, "date", "\"2025\""),
+            SOURCE.replace("date: 2024-01-01", "date: \"2025\"")
+        );
+        assert_eq!(
+            set(SOURCE, "dateModified", "\"2025\""),
+            SOURCE.replace("dateModified: 2024-02-01", "dateModified: \"2025\"")
+        );
+        assert_eq!(
+            set("\"date\": 1\n", "date", "2"),
+            "date: 2\n",
+            "The quoted keys are matched"
+        );
+    }
+
+    #[test]
+    fn set_appends_a_missing_key() {
+        assert_eq!(
+            set(SOURCE, "imageCard", "true"),
+            format!("{SOURCE}imageCard: true\n")
+        );
+        assert_eq!(set("title: a", "draft", "true"), "title: a\ndraft: true\n");
+        assert_eq!(set("", "draft", "true"), "draft: true\n");
+        assert_eq!(
+            set("data:\n  date: 1\n", "date", "2"),
+            "data:\n  date: 1\ndate: 2\n",
+            "The nested keys are not matched"
+        );
+    }
+
+    #[test]
+    fn remove_entries() {
+        assert_eq!(
+            remove(SOURCE, "description"),
+            SOURCE.replace("description: |\n  First line\n\n  Second line\n", "")
+        );
+        assert_eq!(
+            remove(SOURCE, "tags"),
+            SOURCE.replace("tags:\n  - rust\n  - yaml\n", "")
+        );
+        assert_eq!(
+            remove(SOURCE, "date"),
+            SOURCE.replace("date: 2024-01-01\n", "")
+        );
+        assert_eq!(remove(SOURCE, "imageCard"), SOURCE);
+    }
+
+    #[test]
+    fn entry_range_skips_the_trailing_empty_lines() {
+        let lines: Vec<&str> = SOURCE.split_inclusive('\n').collect();
+        assert_eq!(entry_range(&lines, "title"), Some((1, 2)));
+        assert_eq!(entry_range(&lines, "description"), Some((2, 6)));
+        assert_eq!(entry_range(&lines, "tags"), Some((7, 10)));
+        assert_eq!(entry_range(&lines, "date"), Some((11, 12)));
+        assert_eq!(entry_range(&lines, "imageCard"), None);
+    }
+
+    #[test]
+    fn split_the_frontmatter() {
+        assert_eq!(
+            split("---\ntitle: a\n---\nbody\n"),
+            Some(("title: a\n", "body\n"))
+        );
+        assert_eq!(split("---\n---\n"), Some(("", "")));
+        assert_eq!(split("---\ntitle: a\n---"), Some(("title: a\n", "")));
+        assert_eq!(
+            split("--- \ntitle: a\n---  \nbody"),
+            Some(("title: a\n", "body"))
+        );
+    }
+
+    #[test]
+    fn split_crlf() {
+        assert_eq!(
+            split("---\r\ntitle: a\r\n---\r\nbody\r\n"),
+            Some(("title: a\r\n", "body\r\n"))
+        );
+    }
+
+    #[test]
+    fn split_skips_the_bom() {
+        assert_eq!(
+            split("\u{feff}---\ntitle: a\n---\nbody"),
+            Some(("title: a\n", "body"))
+        );
+    }
+
+    #[test]
+    fn split_at_the_first_closing_delimiter() {
+        assert_eq!(
+            split("---\ntitle: a\n---\nbody\n---\nmore\n---\n"),
+            Some(("title: a\n", "body\n---\nmore\n---\n"))
+        );
+        assert_eq!(
+            split("---\ndescription: a --- b\n---\n"),
+            Some(("description: a --- b\n", ""))
+        );
+    }
+
+    #[test]
+    fn split_without_frontmatter() {
+        assert_eq!(split("---\ntitle: a\nbody\n"), None);
+        assert_eq!(split("---"), None);
+        assert_eq!(split(""), None);
+        assert_eq!(split("body\n---\ntitle: a\n---\n"), None);
+        assert_eq!(split("----\ntitle: a\n---\n"), None);
+    }
+}
@@ -32,6 +32,18 @@ pub fn is_new_post(config: &'static Config) -> impl Fn(&Answers) -> bool {
     }
 }
 
+/// Return true if the action is to create new post with a header image, not a header card
+#[logfn_inputs(Info)]
+pub fn is_new_post_image(config: &'static Config) -> impl Fn(&Answers) -> bool {
+    move |answers| {
+        is_new_post(config)(answers)
+            && !answers
+                .get("post_card")
+                .and_then(|card| card.as_bool())
+                .unwrap_or(false)
+    }
+}
+
 /// Autocomplete for files
 /// ### Arguments
 /// * `dir` - Directory to search for files
@@ -93,6 +105,15 @@ pub fn is_modify_post(config: &'static Config) -> impl Fn(&Answers) -> bool {
     }
 }
 
+/// Return true if the action is `delete`
+#[logfn_inputs(Info)]
+pub fn is_delete_post(config: &'static Config) -> impl Fn(&Answers) -> bool {
+    move |answers| {
+        answers.get("action").unwrap().as_list_item().unwrap().text
+            == config.select_action.delete_post_choice
+    }
+}
+
 /// Return true if the action is to create new post
 #[logfn_inputs(Info)]
 pub fn is_show_all_action(config: &'static Config) -> impl Fn(&Answers) -> bool {
@@ -0,0 +1,480 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::{HeaderImageRules, HeaderImageSettings, ImageFormat};
+use crate::errors::{ApcError, ApcResult};
+use image::codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder};
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// An encoded image, ready to be written
+#[derive(Clone)]
+pub struct EncodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: ImageFormat,
+    pub bytes: Vec<u8>,
+}
+
+// The bytes are not useful in the logs
+impl fmt::Debug for EncodedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for EncodedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} {}, {} KB",
+            self.width,
+            self.height,
+            self.format.extension(),
+            (self.bytes.len() + 1023) / 1024
+        )
+    }
+}
+
+impl ImageFormat {
+    /// Return the file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+        }
+    }
+
+    /// Return the decoded image format of the format
+    fn to_image_format(self) -> image::ImageFormat {
+        match self {
+            Self::Webp => image::ImageFormat::WebP,
+            Self::Avif => image::ImageFormat::Avif,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Png => image::ImageFormat::Png,
+        }
+    }
+
+    /// Return the format of the decoded image format, `None` if it cannot be encoded
+    fn from_image_format(format: image::ImageFormat) -> Option<Self> {
+        match format {
+            image::ImageFormat::WebP => Some(Self::Webp),
+            image::ImageFormat::Avif => Some(Self::Avif),
+            image::ImageFormat::Jpeg => Some(Self::Jpeg),
+            image::ImageFormat::Png => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Open and decode the image, with its format.
+/// The EXIF orientation will be applied, so the image is shown the same after stripping the metadata
+/// ### Errors
+/// * If the file cannot be read, or it's not a decodable image (AVIF images are not decodable)
+#[logfn_inputs(Info)]
+pub fn open(path: &Path) -> ApcResult<(DynamicImage, image::ImageFormat)> {
+    let invalid_image =
+        |err: image::ImageError| ApcError::Validation(format!("'{}' {err}", path.display()));
+    let (mut decoder, format) = decoder(path)?;
+    let orientation = decoder.orientation().map_err(invalid_image)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid_image)?;
+    image.apply_orientation(orientation);
+    Ok((image, format))
+}
+
+/// Return the decoder of the image with its format, without decoding the image
+/// ### Errors
+/// * If the file cannot be read, or it's not a decodable image (AVIF images are not decodable)
+fn decoder(path: &Path) -> ApcResult<(Box<dyn ImageDecoder>, image::ImageFormat)> {
+    let reader = ImageReader::open(path)
+        .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?
+        .with_guessed_format()
+        .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+    let format = reader.format().ok_or_else(|| {
+        ApcError::Validation(format!("'{}' is not a supported image", path.display()))
+    })?;
+    if format == image::ImageFormat::Avif {
+        // The `avif` feature of `image` is an encoder only
+        return Err(ApcError::Validation(format!(
+            "'{}' the AVIF images cannot be decoded, convert it to WebP, JPEG or PNG first",
+            path.display()
+        )));
+    }
+    let decoder = reader
+        .into_decoder()
+        .map_err(|err| ApcError::Validation(format!("'{}' {err}", path.display())))?;
+    Ok((Box::new(decoder), format))
+}
+
+/// Encode the image in `format` with `quality` (1-100), the metadata will not be written
+/// ### Errors
+/// * If the image cannot be encoded
+pub fn encode(image: &DynamicImage, format: ImageFormat, quality: u8) -> ApcResult<EncodedImage> {
+    let encode_error = |err: String| {
+        ApcError::Other(format!(
+            "Cannot encode the image to {}: {err}",
+            format.extension()
+        ))
+    };
+    let mut bytes = Vec::new();
+    match format {
+        ImageFormat::Webp => {
+            let image = DynamicImage::ImageRgba8(image.to_rgba8());
+            bytes = webp::Encoder::from_image(&image)
+                .map_err(|err| encode_error(err.to_owned()))?
+                .encode(f32::from(quality))
+                .to_vec();
+        }
+        ImageFormat::Avif => image
+            .to_rgba8()
+            .write_with_encoder(AvifEncoder::new_with_speed_quality(&mut bytes, 6, quality))
+            .map_err(|err| encode_error(err.to_string()))?,
+        // JPEG doesn't support the transparency
+        ImageFormat::Jpeg => image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, quality))
+            .map_err(|err| encode_error(err.to_string()))?,
+        ImageFormat::Png => image
+            .write_with_encoder(PngEncoder::new(&mut bytes))
+            .map_err(|err| encode_error(err.to_string()))?,
+    }
+    Ok(EncodedImage {
+        width: image.width(),
+        height: image.height(),
+        format,
+        bytes,
+    })
+}
+
+/// The processed header image
+pub struct ProcessedHeader {
+    /// The decoded header, after resizing it
+    pub image: DynamicImage,
+    /// The format of the stored header, or of its variants if it's copied in a format that cannot be encoded
+    pub format: ImageFormat,
+    /// The encoded header, `None` if it doesn't need processing, so it can be copied as it is
+    pub encoded: Option<EncodedImage>,
+}
+
+// The pixels are not useful in the logs
+impl fmt::Debug for ProcessedHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessedHeader")
+            .field("width", &self.image.width())
+            .field("height", &self.image.height())
+            .field("format", &self.format)
+            .field("encoded", &self.encoded)
+            .finish()
+    }
+}
+
+/// Process the header image with the header image settings, resize it to the maximum
+/// width and height, and re-encode it without the metadata.
+/// ### Errors
+/// * If the image is not decodable, or cannot be encoded
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn process_header(settings: &HeaderImageSettings, path: &Path) -> ApcResult<ProcessedHeader> {
+    let (image, source_format) = open(path)?;
+    process_image(settings, image, Some(source_format))
+}
+
+/// Return true if the dimensions are larger than the maximum width or height of the settings
+fn is_large(settings: &HeaderImageSettings, width: u32, height: u32) -> bool {
+    settings.max_width.is_some_and(|max| width > max)
+        || settings.max_height.is_some_and(|max| height > max)
+}
+
+/// Return the dimensions of the large header after resizing it, like [`DynamicImage::resize`]
+fn resized_dimensions(settings: &HeaderImageSettings, width: u32, height: u32) -> (u32, u32) {
+    let ratio = f64::min(
+        f64::from(settings.max_width.unwrap_or(u32::MAX)) / f64::from(width),
+        f64::from(settings.max_height.unwrap_or(u32::MAX)) / f64::from(height),
+    );
+    let scale = |side: u32| ((f64::from(side) * ratio).round() as u32).max(1);
+    (scale(width), scale(height))
+}
+
+/// Return the format that the header will be encoded to, `None` if it will be copied as it is.
+/// `source_format` is the format of the source file, `None` if it cannot be copied
+fn encoding_format(
+    settings: &HeaderImageSettings,
+    source_format: Option<image::ImageFormat>,
+    is_large: bool,
+) -> Option<ImageFormat> {
+    let is_copied = source_format.is_some_and(|source_format| {
+        !is_large
+            && !settings.strip_metadata
+            && settings.format.map_or(true, |format| {
+                ImageFormat::from_image_format(source_format) == Some(format)
+            })
+    });
+    (!is_copied).then(|| header_format(settings, source_format))
+}
+
+/// Return the format of the stored header and its variants, the source format if it's not configured.
+/// The formats that cannot be encoded (e.g. GIF) have PNG variants
+fn header_format(
+    settings: &HeaderImageSettings,
+    source_format: Option<image::ImageFormat>,
+) -> ImageFormat {
+    settings
+        .format
+        .or(source_format.and_then(ImageFormat::from_image_format))
+        .unwrap_or(ImageFormat::Png)
+}
+
+/// Process the decoded header with the header image settings, like [`process_header`].
+/// `source_format` is the format of the source file that can be copied as it is,
+/// `None` to always encode the header.
+/// ### Errors
+/// * If the image cannot be encoded
+#[logfn(Debug)]
+pub fn process_image(
+    settings: &HeaderImageSettings,
+    mut image: DynamicImage,
+    source_format: Option<image::ImageFormat>,
+) -> ApcResult<ProcessedHeader> {
+    let is_large = is_large(settings, image.width(), image.height());
+    let Some(format) = encoding_format(settings, source_format, is_large) else {
+        return Ok(ProcessedHeader {
+            image,
+            format: header_format(settings, source_format),
+            encoded: None,
+        });
+    };
+    if is_large {
+        let (width, height) = resized_dimensions(settings, image.width(), image.height());
+        image = image.resize(width, height, FilterType::Lanczos3);
+    }
+    Ok(ProcessedHeader {
+        encoded: Some(encode(&image, format, settings.quality)?),
+        image,
+        format,
+    })
+}
+
+/// Encode the width variants of the header (`variants` setting), in the header format.
+/// The widths that are not smaller than the header width will be skipped
+/// ### Errors
+/// * If a variant cannot be encoded
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn header_variants(
+    settings: &HeaderImageSettings,
+    header: &ProcessedHeader,
+) -> ApcResult<Vec<EncodedImage>> {
+    let mut widths: Vec<u32> = settings
+        .variants
+        .iter()
+        .copied()
+        .filter(|width| *width < header.image.width())
+        .collect();
+    widths.sort_unstable();
+    widths.dedup();
+    widths
+        .into_iter()
+        .map(|width| {
+            let variant = header.image.resize(width, u32::MAX, FilterType::Lanczos3);
+            encode(&variant, header.format, settings.quality)
+        })
+        .collect()
+}
+
+/// Return the blurhash placeholder of the image, computed from a small thumbnail of it
+/// ### Errors
+/// * If the blurhash cannot be computed
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn placeholder(image: &DynamicImage) -> ApcResult<String> {
+    let thumbnail = image.thumbnail(32, 32).to_rgba8();
+    blurhash::encode(
+        4,
+        3,
+        thumbnail.width(),
+        thumbnail.height(),
+        thumbnail.as_raw(),
+    )
+    .map_err(|err| ApcError::Other(format!("Cannot compute the header placeholder: {err}")))
+}
+
+/// Return the dimensions of AVIF image from its `ispe` (image spatial extents) box
+fn avif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let start = bytes.windows(4).position(|window| window == b"ispe")? + 4;
+    // The box version and flags, then the width and height
+    let extents = bytes.get(start + 4..start + 12)?;
+    Some((
+        u32::from_be_bytes(extents[..4].try_into().ok()?),
+        u32::from_be_bytes(extents[4..].try_into().ok()?),
+    ))
+}
+
+/// The properties of the stored header image, to check the rules on them
+#[derive(Debug)]
+pub struct HeaderInfo {
+    pub width: u32,
+    pub height: u32,
+    pub format: image::ImageFormat,
+    /// The size of the header, `None` if it's not encoded yet
+    pub bytes: Option<u64>,
+}
+
+impl HeaderInfo {
+    /// Read the header properties from its file, without decoding the whole image
+    /// ### Errors
+    /// * If the file cannot be read, or it's not a supported image
+    #[logfn_inputs(Info)]
+    #[logfn(Debug)]
+    pub fn from_file(path: &Path) -> ApcResult<Self> {
+        let reader = ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+        let format = reader.format().ok_or_else(|| {
+            ApcError::Validation(format!("'{}' is not a supported image", path.display()))
+        })?;
+        let (width, height) = if format == image::ImageFormat::Avif {
+            // The AVIF decoder is not supported, so read the dimensions from the container
+            fs::read(path)
+                .ok()
+                .and_then(|bytes| avif_dimensions(&bytes))
+                .ok_or_else(|| {
+                    ApcError::Validation(format!(
+                        "'{}' cannot read the AVIF image dimensions",
+                        path.display()
+                    ))
+                })?
+        } else {
+            reader
+                .into_dimensions()
+                .map_err(|err| ApcError::Validation(format!("'{}' {err}", path.display())))?
+        };
+        let bytes = fs::metadata(path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?
+            .len();
+        Ok(Self {
+            width,
+            height,
+            format,
+            bytes: Some(bytes),
+        })
+    }
+
+    /// Return the properties of the encoded header
+    pub fn from_encoded(image: &EncodedImage) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            format: image.format.to_image_format(),
+            bytes: Some(image.bytes.len() as u64),
+        }
+    }
+
+    /// Return the properties of the header that will be stored from `path`, after processing it.
+    /// The header is not decoded nor encoded, so the size of the header that will be encoded is unknown
+    /// ### Errors
+    /// * If the image is not decodable
+    #[logfn_inputs(Info)]
+    #[logfn(Debug)]
+    pub fn processed(settings: &HeaderImageSettings, path: &Path) -> ApcResult<Self> {
+        let (mut decoder, source_format) = decoder(path)?;
+        let orientation = decoder
+            .orientation()
+            .map_err(|err| ApcError::Validation(format!("'{}' {err}", path.display())))?;
+        let (width, height) = match orientation {
+            // The header will be rotated by its EXIF orientation
+            Orientation::Rotate90
+            | Orientation::Rotate270
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH => {
+                let (width, height) = decoder.dimensions();
+                (height, width)
+            }
+            _ => decoder.dimensions(),
+        };
+        let is_large = is_large(settings, width, height);
+        let Some(format) = encoding_format(settings, Some(source_format), is_large) else {
+            return Self::from_file(path);
+        };
+        let (width, height) = if is_large {
+            resized_dimensions(settings, width, height)
+        } else {
+            (width, height)
+        };
+        Ok(Self {
+            width,
+            height,
+            format: format.to_image_format(),
+            bytes: None,
+        })
+    }
+
+    /// Check the header against the rules, and return the first broken rule
+    pub fn check(&self, rules: &HeaderImageRules) -> Result<(), String> {
+        let format = ImageFormat::from_image_format(self.format);
+        if !rules.formats.is_empty()
+            && !format.is_some_and(|format| rules.formats.contains(&format))
+        {
+            return Err(format!(
+                "The header format `{}` is not allowed, the allowed formats: {}",
+                self.format.extensions_str().first().unwrap_or(&"unknown"),
+                rules
+                    .formats
+                    .iter()
+                    .map(|format| format.extension())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        let dimensions = format!("The header is {}x{}", self.width, self.height);
+        if let Some(min_width) = rules.min_width.filter(|min| self.width < *min) {
+            return Err(format!("{dimensions}, the minimum width is {min_width}"));
+        }
+        if let Some(min_height) = rules.min_height.filter(|min| self.height < *min) {
+            return Err(format!("{dimensions}, the minimum height is {min_height}"));
+        }
+        if let Some(max_width) = rules.max_width.filter(|max| self.width > *max) {
+            return Err(format!("{dimensions}, the maximum width is {max_width}"));
+        }
+        if let Some(max_height) = rules.max_height.filter(|max| self.height > *max) {
+            return Err(format!("{dimensions}, the maximum height is {max_height}"));
+        }
+        if let Some(aspect_ratio) = rules.aspect_ratio {
+            let ratio = self.width as f32 / self.height.max(1) as f32;
+            if (ratio - aspect_ratio).abs() > aspect_ratio * rules.aspect_ratio_tolerance {
+                return Err(format!(
+                    "{dimensions} ({ratio:.2}:1), the aspect ratio should be {aspect_ratio:.2}:1"
+                ));
+            }
+        }
+        if let Some((bytes, max_bytes)) = self
+            .bytes
+            .zip(rules.max_bytes)
+            .filter(|(bytes, max)| bytes > max)
+        {
+            return Err(format!(
+                "The header size is {bytes} bytes, the maximum is {max_bytes} bytes"
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::frontmatter;
+use super::transaction::Change;
+use super::{is_header_variant, slug_updater, Post};
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use std::fs;
+use std::path::Path;
+
+/// Return true if the character can be a part of a slug
+fn is_slug_char(chr: char) -> bool {
+    chr.is_alphanumeric() || chr == '-' || chr == '_'
+}
+
+/// Replace the `old` link with the `new` one in `text`.
+/// The link will not be replaced if it's followed by a slug character (unless it's a directory link),
+/// so `/blog/rust` will not replace `/blog/rust-cli`
+pub fn replace_link(text: &str, old: &str, new: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(old) {
+        let after = &rest[idx + old.len()..];
+        result.push_str(&rest[..idx]);
+        if !old.ends_with('/') && after.starts_with(is_slug_char) {
+            result.push_str(old);
+        } else {
+            result.push_str(new);
+        }
+        rest = after;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Replace the images directory links of the renamed post in `text`, with the names of
+/// its header and the header variants (`{slug}-header.{extension}`, `{slug}-header-{width}.{extension}`),
+/// they are renamed with the post. The other images keep their names
+fn replace_images_links(config: &Config, text: &str, old_slug: &str, new_slug: &str) -> String {
+    let old_dir = format!("{}{old_slug}/", config.images_site_path);
+    let new_dir = format!("{}{new_slug}/", config.images_site_path);
+    let old_header = format!("{}-header", slug_updater(old_slug));
+    let new_header = format!("{}-header", slug_updater(new_slug));
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(&old_dir) {
+        result.push_str(&rest[..idx]);
+        result.push_str(&new_dir);
+        rest = &rest[idx + old_dir.len()..];
+        let name_end = rest
+            .find(|chr: char| !is_slug_char(chr) && chr != '.')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let is_header = name
+            .rsplit_once('.')
+            .is_some_and(|(stem, _)| stem == old_header);
+        if is_header || is_header_variant(old_slug, name) {
+            result.push_str(&new_header);
+            rest = &rest[old_header.len()..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Rewrite the links of the renamed post in `body`, its link and its images links
+pub fn rewrite(config: &Config, body: &str, old_slug: &str, new_slug: &str) -> String {
+    let body = replace_link(
+        body,
+        &format!("{}{old_slug}", config.blog_site_path),
+        &format!("{}{new_slug}", config.blog_site_path),
+    );
+    replace_images_links(config, &body, old_slug, new_slug)
+}
+
+/// Return the changes to rewrite the links of the renamed post in the other posts bodies.
+/// The `skip` post file will not be scanned, it's the renamed post itself.
+/// ### Errors
+/// * If cannot read the posts directory or one of the posts
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn rewrite_changes(
+    config: &'static Config,
+    skip: &Path,
+    old_slug: &str,
+    new_slug: &str,
+) -> ApcResult<Vec<Change>> {
+    let mut changes = Vec::new();
+    for path in Post::paths(config)? {
+        if path == skip {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+        // The files without frontmatter are not posts
+        let Some((_, body)) = frontmatter::split(&content) else {
+            continue;
+        };
+        let new_body = rewrite(config, body, old_slug, new_slug);
+        if new_body != body {
+            changes.push(Change::Write {
+                contents: format!("{}{new_body}", &content[..content.len() - body.len()]),
+                path,
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Return the images references in `content`, the site paths that start with `images_site_path`.
+/// The query and the fragment of the reference will be removed
+pub fn image_references<'a>(config: &Config, content: &'a str) -> Vec<&'a str> {
+    content
+        .match_indices(&config.images_site_path)
+        .filter(|(idx, _)| {
+            // Should not be a part of another path, like `/assets/images/`
+            !content[..*idx].ends_with(|chr: char| chr.is_alphanumeric() || chr == '/')
+        })
+        .map(|(idx, _)| {
+            let reference = &content[idx..];
+            let end = reference
+                .find(|chr: char| {
+                    chr.is_whitespace()
+                        || matches!(chr, ')' | ']' | '"' | '\'' | '>' | '<' | '?' | '#')
+                })
+                .unwrap_or(reference.len());
+            &reference[..end]
+        })
+        .filter(|reference| reference.len() > config.images_site_path.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_link_keeps_the_longer_slugs() {
+        let text = "[a](/blog/rust) [b](/blog/rust-cli) [c](/blog/rust#intro)";
+        assert_eq!(
+            replace_link(text, "/blog/rust", "/blog/go"),
+            "[a](/blog/go) [b](/blog/rust-cli) [c](/blog/go#intro)"
+        );
+    }
+
+    #[test]
+    fn rewrite_renames_the_header_links() {
+        let config = Config::default();
+        let body = "![h](/images/hello/hello-header.png)\n\
+                    ![v](/images/hello/hello-header-480.webp)\n\
+                    ![a](/images/hello/hello-header-notes.png)\n\
+                    ![d](/images/hello/diagram.png)\n\
+                    [p](/blog/hello)";
+        assert_eq!(
+            rewrite(&config, body, "hello", "hi-rust"),
+            "![h](/images/hi-rust/hi-rust-header.png)\n\
+             ![v](/images/hi-rust/hi-rust-header-480.webp)\n\
+             ![a](/images/hi-rust/hello-header-notes.png)\n\
+             ![d](/images/hi-rust/diagram.png)\n\
+             [p](/blog/hi-rust)"
+        );
+    }
+
+    #[test]
+    fn rewrite_keeps_the_other_posts_images() {
+        let config = Config::default();
+        let body = "![o](/images/hello-world/hello-world-header.png)";
+        assert_eq!(rewrite(&config, body, "hello", "hi"), body);
+    }
+}
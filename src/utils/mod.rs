@@ -15,19 +15,26 @@
 //     You should have received a copy of the GNU General Public License
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod card;
+pub mod frontmatter;
 pub mod helpers;
+pub mod images;
+pub mod links;
 mod post;
 pub mod properties;
 pub mod questions;
+pub mod redirects;
+pub mod transaction;
 pub mod validators;
 
 use chrono::{DateTime, Local, NaiveDate};
 pub use post::*;
 
-use crate::config::Config;
+use crate::config::{CardFormat, Config};
 use crate::errors::{ApcError, ApcResult};
 use std::fs;
 use std::path::{Path, PathBuf};
+use transaction::Change;
 
 /// Update the slug to correct one
 pub fn slug_updater(slug: &str) -> String {
@@ -95,7 +102,7 @@ pub fn home_dir() -> String {
 #[logfn_inputs(Info)]
 #[logfn(Debug)]
 #[allow(clippy::manual_strip)]
-fn replace_tilde_with_home_dir(path: &str) -> String {
+pub fn replace_tilde_with_home_dir(path: &str) -> String {
     if path.starts_with('~') {
         format!("{}{}", home_dir(), &path[1..])
     } else {
@@ -103,60 +110,216 @@ fn replace_tilde_with_home_dir(path: &str) -> String {
     }
 }
 
-/// Move new post headeer to images directory, return new post headeer image path
+/// Return the images directory of the post
+pub fn post_images_dir(config: &Config, slug: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}/", config.images_path, slug_updater(slug)))
+}
+
+/// Check that the slug is not used by another post, with its post file or its images directory
+/// ### Errors
+/// * If the post file or the images directory of the slug is already exists
 #[logfn_inputs(Info)]
 #[logfn(Debug)]
-pub fn copy_post_header(config: &Config, slug: &str, new_post_header: &str) -> ApcResult<String> {
-    let full_new_header_path = full_path(&replace_tilde_with_home_dir(new_post_header));
-    let extension = Path::new(&full_new_header_path)
-        .extension()
-        .map(|os_str| os_str.to_str().unwrap_or("png"))
-        .unwrap_or("png");
+pub fn check_slug_available(config: &Config, slug: &str) -> ApcResult<()> {
     let slug = slug_updater(slug);
-    let slug_dir = PathBuf::from(format!("{}{slug}/", config.images_path));
-    let filename = format!("{slug}-header.{extension}");
-    let to_path = format!("{}{slug}/{filename}", config.images_path,);
-    if !slug_dir.exists() {
-        fs::create_dir(slug_dir).map_err(|err| {
-            log::error!("{:?}", err);
-            ApcError::FileSystem(err.to_string())
-        })?;
+    let post_path = to_post_path(config, &slug);
+    let images_dir = post_images_dir(config, &slug);
+    if Path::new(&post_path).exists() {
+        Err(ApcError::Validation(format!(
+            "The slug `{slug}` is already used by '{post_path}'"
+        )))
+    } else if images_dir.exists() {
+        Err(ApcError::Validation(format!(
+            "The slug `{slug}` is already used by '{}' images directory",
+            images_dir.display()
+        )))
+    } else {
+        Ok(())
     }
-    fs::copy(full_new_header_path, &to_path).map_err(|err| {
-        log::error!("{:?}", err);
-        ApcError::FileSystem(err.to_string())
-    })?;
-    Ok(to_path)
 }
 
+/// Return the header path of the post in images directory, `{slug}-header.{extension}`
+pub fn post_header_path(config: &Config, slug: &str, extension: &str) -> String {
+    let slug = slug_updater(slug);
+    format!("{}{slug}/{slug}-header.{extension}", config.images_path)
+}
+
+/// Return the extension of the path, `png` if it doesn't have one
+pub fn path_extension(path: &str) -> &str {
+    Path::new(path)
+        .extension()
+        .and_then(|os_str| os_str.to_str())
+        .unwrap_or("png")
+}
+
+/// Return the path of the header variant in images directory, `{slug}-header-{width}.{extension}`
+pub fn post_header_variant_path(
+    config: &Config,
+    slug: &str,
+    width: u32,
+    extension: &str,
+) -> String {
+    let slug = slug_updater(slug);
+    format!(
+        "{}{slug}/{slug}-header-{width}.{extension}",
+        config.images_path
+    )
+}
+
+/// Return true if the file name is a header variant of the post, `{slug}-header-{width}.{extension}`
+pub fn is_header_variant(slug: &str, file_name: &str) -> bool {
+    file_name
+        .strip_prefix(&format!("{}-header-", slug_updater(slug)))
+        .and_then(|rest| rest.split('.').next())
+        .is_some_and(|width| !width.is_empty() && width.chars().all(|chr| chr.is_ascii_digit()))
+}
+
+/// The post header in images directory, with the changes to write it
+#[derive(Debug)]
+pub struct PostHeader {
+    /// Path of the header in images directory
+    pub path: String,
+    /// The `srcset` of the header variants and the header, `None` if there is no variants
+    pub srcset: Option<String>,
+    /// The blurhash placeholder of the header, `None` if it's disabled
+    pub placeholder: Option<String>,
+    /// The changes to write the header and its variants
+    pub changes: Vec<Change>,
+}
+
+/// Return the new post header in images directory, with the changes to copy the new post header
+/// to it and to write its variants, and its placeholder.
+/// The header will be processed with `header_image_settings`.
+/// ### Errors
+/// * If the header is not decodable image, or cannot be processed
+/// * If the encoded header is larger than `max_bytes` rule, its size is not known before encoding it
 #[logfn_inputs(Info)]
 #[logfn(Debug)]
-pub fn parse_string(str_string: &str) -> ApcResult<String> {
-    let str_string = str_string.trim();
-    if str_string.starts_with('"') && str_string.ends_with('"') {
-        Ok(str_string.trim_matches('"').chars().collect())
-    } else {
-        Err(ApcError::PostProperties(format!(
-            "`{str_string:?}` invalid string propertie, should start and end with '\"'"
-        )))
+pub fn copy_post_header(
+    config: &Config,
+    slug: &str,
+    new_post_header: &str,
+) -> ApcResult<PostHeader> {
+    let full_new_header_path = full_path(&replace_tilde_with_home_dir(new_post_header));
+    let header = images::process_header(
+        &config.header_image_settings,
+        Path::new(&full_new_header_path),
+    )?;
+    if let Some(image) = &header.encoded {
+        images::HeaderInfo::from_encoded(image)
+            .check(&config.header_image_rules)
+            .map_err(|err| ApcError::Validation(format!("'{full_new_header_path}' {err}")))?;
     }
+    write_post_header(config, slug, header, &full_new_header_path)
 }
 
+/// Return the rendered header card of the post in images directory, with the changes to write it.
+/// The PNG card will be processed with `header_image_settings` like the header images,
+/// and the SVG card will be written as it is, without variants.
+/// ### Errors
+/// * If the card cannot be rendered, or cannot be processed
 #[logfn_inputs(Info)]
 #[logfn(Debug)]
-pub fn parse_str_vec(str_vec: &str) -> ApcResult<Vec<String>> {
-    let str_vec = str_vec.trim();
-    if str_vec.starts_with('[') && str_vec.ends_with(']') {
-        str_vec
-            .trim_matches(|c| "[]".contains(c))
-            .split(',')
-            .map(parse_string)
-            .collect()
-    } else {
-        Err(ApcError::PostProperties(format!(
-            "`{str_vec:?}` invalid list propertie, should start and end with '\"'"
-        )))
+pub fn card_post_header(
+    config: &Config,
+    slug: &str,
+    title: &str,
+    tags: &[String],
+) -> ApcResult<PostHeader> {
+    let settings = &config.header_card_settings;
+    let svg = card::render(settings, title, tags)?;
+    match settings.format {
+        CardFormat::Png => {
+            let image = card::rasterize(settings, &svg)?;
+            let header = images::process_image(&config.header_image_settings, image, None)?;
+            write_post_header(config, slug, header, "")
+        }
+        CardFormat::Svg => {
+            let placeholder = config
+                .header_image_settings
+                .placeholder
+                .then(|| images::placeholder(&card::rasterize(settings, &svg)?))
+                .transpose()?;
+            let path = post_header_path(config, slug, "svg");
+            let slug_dir = post_images_dir(config, slug);
+            let mut changes = Vec::new();
+            if !slug_dir.exists() {
+                changes.push(Change::CreateDir(slug_dir));
+            }
+            changes.push(Change::Write {
+                path: PathBuf::from(&path),
+                contents: svg,
+            });
+            Ok(PostHeader {
+                path,
+                srcset: None,
+                placeholder,
+                changes,
+            })
+        }
+    }
+}
+
+/// Return the processed header in images directory, with the changes to write it and its variants,
+/// and its placeholder. The header will be copied from `source_path` if it doesn't need encoding.
+fn write_post_header(
+    config: &Config,
+    slug: &str,
+    header: images::ProcessedHeader,
+    source_path: &str,
+) -> ApcResult<PostHeader> {
+    let site_path = |path: &str| path.replace(&config.images_path, &config.images_site_path);
+    let slug_dir = post_images_dir(config, slug);
+    let mut changes = Vec::new();
+    if !slug_dir.exists() {
+        changes.push(Change::CreateDir(slug_dir));
+    }
+    let variants = images::header_variants(&config.header_image_settings, &header)?;
+    let placeholder = config
+        .header_image_settings
+        .placeholder
+        .then(|| images::placeholder(&header.image))
+        .transpose()?;
+    let header_width = header.image.width();
+    let path = match header.encoded {
+        Some(image) => {
+            let to_path = post_header_path(config, slug, image.format.extension());
+            changes.push(Change::WriteImage {
+                path: PathBuf::from(&to_path),
+                image,
+            });
+            to_path
+        }
+        None => {
+            let to_path = post_header_path(config, slug, path_extension(source_path));
+            changes.push(Change::Copy {
+                from: PathBuf::from(source_path),
+                to: PathBuf::from(&to_path),
+            });
+            to_path
+        }
+    };
+
+    let mut srcset = Vec::new();
+    for variant in variants {
+        let variant_path =
+            post_header_variant_path(config, slug, variant.width, variant.format.extension());
+        srcset.push(format!("{} {}w", site_path(&variant_path), variant.width));
+        changes.push(Change::WriteImage {
+            path: PathBuf::from(variant_path),
+            image: variant,
+        });
     }
+    let srcset = (!srcset.is_empty()).then(|| {
+        srcset.push(format!("{} {header_width}w", site_path(&path)));
+        srcset.join(", ")
+    });
+    Ok(PostHeader {
+        path,
+        srcset,
+        placeholder,
+        changes,
+    })
 }
 
 #[logfn_inputs(Info)]
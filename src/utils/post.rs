@@ -18,17 +18,23 @@
 use crate::app::Action;
 use crate::config::Config;
 use crate::errors::{ApcError, ApcResult};
-use crate::utils::{properties, slug_updater};
+use crate::utils::{frontmatter, properties, slug_updater};
 use crate::{utils, CONFIG};
 use chrono::prelude::*;
 use requestty::Answers;
-use std::ffi::OsString;
+use serde_yaml::{Mapping, Value};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::frontmatter::Frontmatter;
 use super::helpers::get_modifing_post;
 use super::properties::{ExtractProp, ModifyStatus, Props};
-use super::{copy_post_header, full_path, parse_bool, replace_tilde_with_home_dir};
+use super::transaction::{Change, Transaction};
+use super::{
+    card_post_header, check_slug_available, copy_post_header, full_path, is_header_variant,
+    parse_bool, path_extension, post_header_path, post_images_dir, replace_tilde_with_home_dir,
+};
+use super::{links, redirects};
 
 #[derive(Debug, Clone, Copy)]
 pub enum PostProperties {
@@ -142,11 +148,22 @@ pub struct Post {
     pub description: String,
     pub tags: Vec<String>,
     pub image_path: String,
+    /// The `srcset` of the header variants, `None` if the header doesn't have variants
+    pub image_srcset: Option<String>,
+    /// The blurhash placeholder of the header, `None` if the header doesn't have one
+    pub image_placeholder: Option<String>,
     #[educe(Default(expression = "chrono::offset::Local::now()"))]
     pub date: DateTime<Local>,
     #[educe(Default(expression = "chrono::offset::Local::now()"))]
     pub date_modified: DateTime<Local>,
     pub link: String,
+    /// The frontmatter source from the post file, to keep its unknown keys, comments and order
+    pub frontmatter: Option<String>,
+    /// Render the header card from the title and tags, instead of using `image_path` as header
+    pub header_card: bool,
+    /// The header is a rendered card (`imageCard` property), it will be rendered again
+    /// when the title or the tags are modified
+    pub image_card: bool,
 }
 
 impl Post {
@@ -160,7 +177,12 @@ impl Post {
             .expect("Create post action should have a slug with 'post_slug' name")
             .as_string()
             .expect("'post_slug' should be string");
-        Ok(Action::Create(Post::try_new(
+        check_slug_available(config, slug)?;
+        let header_card = answers
+            .get("post_card")
+            .and_then(|card| card.as_bool())
+            .unwrap_or(false);
+        let mut post = Post::try_new(
             config,
             answers
                 .get("post_title")
@@ -170,7 +192,11 @@ impl Post {
                 .trim()
                 .to_owned(),
             slug.to_owned(),
-            true,
+            // Only the `new` command can set the draft status
+            answers
+                .get("post_draft")
+                .and_then(|draft| draft.as_bool())
+                .unwrap_or(true),
             answers
                 .get("post_description")
                 .unwrap()
@@ -182,14 +208,20 @@ impl Post {
                 answers.get("post_tags").unwrap().as_string().unwrap(),
                 config.create_post_settings.separated_tags_by,
             ),
-            utils::copy_post_header(
-                config,
-                slug,
-                answers.get("post_image").unwrap().as_string().unwrap(),
-            )?,
+            // Will be copied to images directory when the post is created,
+            // there is no header image if the header card will be rendered
+            if header_card {
+                String::new()
+            } else {
+                full_path(&replace_tilde_with_home_dir(
+                    answers.get("post_image").unwrap().as_string().unwrap(),
+                ))
+            },
             chrono::offset::Local::now(),
             chrono::offset::Local::now(),
-        )?))
+        )?;
+        post.header_card = header_card;
+        Ok(Action::Create(post))
     }
 
     /// Return a modify action with a new post from answers
@@ -203,22 +235,20 @@ impl Post {
         let new_props = Props::modified_from_answers(answers)?;
 
         if post.slug != new_props.slug {
-            // Check if the new slug is already existing or not
-            if fs::read_dir(&config.posts_path)
-                .map_err(|err| ApcError::FileSystem(format!("'{}' {}", config.posts_path, err)))?
-                .collect::<Result<Vec<fs::DirEntry>, std::io::Error>>()
-                .map_err(|err| {
-                    ApcError::FileSystem(format!("File in `{}`: {}", config.posts_path, err))
-                })?
-                .iter()
-                .all(|entry| entry.file_name() == OsString::from(format!("{}.md", new_props.slug)))
-            {
-                return Err(ApcError::PostProperties(
-                    "The new slug is already existing".to_owned(),
-                ));
-            }
+            check_slug_available(config, &new_props.slug)?;
         }
 
+        // The card header shows the title and the tags, so it's rendered again with them
+        let is_same_image = new_props.image_path == post.image_path
+            || fs::canonicalize(&new_props.image_path).ok()
+                == fs::canonicalize(&post.image_path).ok();
+        let is_card_modified =
+            post.image_card && (new_props.title != post.title || new_props.tags != post.tags);
+        post.header_card = answers
+            .get("new_post_card")
+            .and_then(|card| card.as_bool())
+            .unwrap_or(false)
+            || (is_card_modified && is_same_image);
         post.title = new_props.title;
         post.description = new_props.desctiption;
         post.is_draft = new_props.draft;
@@ -233,6 +263,28 @@ impl Post {
         })
     }
 
+    /// Return a delete action with the post from answers
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn delete_action(config: &'static Config, answers: &Answers) -> ApcResult<Action> {
+        let slug = answers
+            .get("delete_post_file")
+            .expect("Delete post action should have a slug with 'delete_post_file' name")
+            .as_string()
+            .expect("'delete_post_file' should be string");
+        Ok(Action::Delete {
+            // Only the slug is needed, so a broken post (e.g. its header is missing) can be deleted
+            post: Post {
+                slug: slug_updater(slug),
+                ..Default::default()
+            },
+            confirmed: answers
+                .get("delete_confirm")
+                .and_then(|confirm| confirm.as_bool())
+                .unwrap_or(false),
+        })
+    }
+
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
     #[allow(clippy::too_many_arguments)]
@@ -249,7 +301,8 @@ impl Post {
     ) -> ApcResult<Self> {
         let modified_slug = slug_updater(&slug);
 
-        if !Path::new(&image_path).exists() {
+        // An empty image path is for the posts that their header card will be rendered
+        if !image_path.is_empty() && !Path::new(&image_path).exists() {
             return Err(ApcError::Validation(format!(
                 "The image `{}` doesn't exist",
                 image_path
@@ -267,126 +320,247 @@ impl Post {
             date,
             date_modified,
             link: format!("{}{}", config.blog_site_path, modified_slug),
+            image_srcset: None,
+            image_placeholder: None,
+            frontmatter: None,
+            header_card: false,
+            image_card: false,
         })
     }
 
     /// Update post slug.
-    /// This method will update `slug`, `link`, `post_file_name`
+    /// This method will update `slug` and `link`, and return the post file rename change
     #[logfn_inputs(Info)]
     #[logfn(Debug)]
-    pub fn update_slug(&mut self, config: &'static Config, new_slug: &str) -> ApcResult<()> {
+    pub fn update_slug(&mut self, config: &'static Config, new_slug: &str) -> Vec<Change> {
         let new_slug = slug_updater(new_slug);
+        let mut changes = Vec::new();
         if self.slug != new_slug {
-            fs::rename(
-                format!("{}{}.md", config.posts_path, self.slug),
-                format!("{}{}.md", config.posts_path, new_slug),
-            )
-            .map_err(|err| ApcError::FileSystem(err.to_string()))?;
+            let old_path = self.path(config);
             self.slug = new_slug;
             self.link = format!("{}{}", config.blog_site_path, self.slug);
+            changes.push(Change::Rename {
+                from: old_path,
+                to: self.path(config),
+            });
         }
-        Ok(())
+        changes
     }
 
-    /// Update post image.
-    /// This method will update `image_path` and remove old images folder to create new one with new slug.
+    /// Update post images.
+    /// This method will update `image_path`, `image_srcset` and `image_placeholder`, and return the changes to move the
+    /// images directory to the new slug, and to replace the header and its variants with `new_image`
+    /// if it's a new one, or with the rendered header card if `header_card` is set.
     /// ## Notes:
     /// - This method should be called after update the slug.
+    /// ## Errors:
+    /// - If cannot read the old images directory, or it's contain a directory.
+    /// - If the new header cannot be processed.
     #[logfn_inputs(Info)]
     #[logfn(Debug)]
-    pub fn update_images(&mut self, config: &'static Config, old_slug: &str) -> ApcResult<()> {
-        if self.slug != old_slug {
-            let old_image_path = Path::new(&self.image_path);
-            let new_image_path = copy_post_header(config, &self.slug, &self.image_path)?;
+    pub fn update_images(
+        &mut self,
+        config: &'static Config,
+        old_slug: &str,
+        new_image: &str,
+    ) -> ApcResult<Vec<Change>> {
+        let old_header = PathBuf::from(&self.image_path);
+        let is_new_header = self.header_card
+            || (new_image != self.image_path
+                && fs::canonicalize(new_image).ok() != fs::canonicalize(&old_header).ok());
+        let old_dir = post_images_dir(config, old_slug);
+        let new_dir = post_images_dir(config, &self.slug);
+        let old_images = if old_dir.exists() || self.slug != old_slug {
+            fs::read_dir(&old_dir)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|err| ApcError::FileSystem(format!("`{}` {err}", old_dir.display())))?
+        } else {
+            Vec::new()
+        };
+        if let Some(dir) = old_images.iter().find(|path| !path.is_file()) {
+            return Err(ApcError::Other(format!(
+                "Images directory cannot contain directory: `{}`",
+                dir.display()
+            )));
+        }
+        let is_old_variant = |path: &Path| {
+            path.file_name()
+                .is_some_and(|name| is_header_variant(old_slug, &name.to_string_lossy()))
+        };
 
-            for image in fs::read_dir(
-                old_image_path
-                    .parent()
-                    .expect("The parent is slug directory"),
-            )
-            .map_err(|err| ApcError::FileSystem(format!("`{}` {err}", old_image_path.display())))?
-            {
-                let image = image.map_err(|err| ApcError::FileSystem(err.to_string()))?;
-                let image_path = image.path();
-                if image_path.is_file() {
-                    // This mean the image its not post header
-                    if image_path != old_image_path {
-                        fs::rename(
-                            &image_path,
-                            format!(
-                                "{}{}/{}",
-                                config.images_path,
-                                self.slug,
-                                image
-                                    .path()
-                                    .file_name()
-                                    .expect("Was check is file")
-                                    .to_str()
-                                    .ok_or_else(|| ApcError::FileSystem(format!(
-                                        "Invalid image name: {}",
-                                        image_path.display()
-                                    )))?
-                            ),
-                        )
-                        .map_err(|err| ApcError::FileSystem(err.to_string()))?
-                    }
-                } else {
-                    return Err(ApcError::Other(format!(
-                        "Images directory cannot contain directory: `{}`",
-                        image_path.display()
-                    )));
+        let (new_header, mut header_changes) = if is_new_header {
+            let header = if self.header_card {
+                card_post_header(config, &self.slug, &self.title, &self.tags)?
+            } else {
+                copy_post_header(config, &self.slug, new_image)?
+            };
+            self.image_srcset = header.srcset;
+            self.image_placeholder = header.placeholder;
+            self.image_card = self.header_card;
+            (header.path, header.changes)
+        } else if self.slug != old_slug {
+            // Move the header as it is, without processing it again
+            let new_header = post_header_path(config, &self.slug, path_extension(&self.image_path));
+            let mut changes = Vec::new();
+            if !new_dir.exists() {
+                changes.push(Change::CreateDir(new_dir.clone()));
+            }
+            changes.push(Change::Rename {
+                from: old_header.clone(),
+                to: PathBuf::from(&new_header),
+            });
+            // The variants will be renamed with the other images
+            let header_site_path = |slug: &str| {
+                let slug = slug_updater(slug);
+                format!("{}{slug}/{slug}-header", config.images_site_path)
+            };
+            self.image_srcset = self.image_srcset.as_ref().map(|srcset| {
+                srcset.replace(&header_site_path(old_slug), &header_site_path(&self.slug))
+            });
+            (new_header, changes)
+        } else {
+            (self.image_path.clone(), Vec::new())
+        };
+        let new_header = PathBuf::from(new_header);
+        let mut changes = Vec::new();
+
+        if self.slug != old_slug {
+            changes.append(&mut header_changes);
+            for image_path in old_images {
+                if image_path == old_header || (is_new_header && is_old_variant(&image_path)) {
+                    // The old header and its variants are replaced
+                    continue;
                 }
+                let file_name = image_path
+                    .file_name()
+                    .expect("Was check is file")
+                    .to_string_lossy()
+                    .into_owned();
+                let new_name = if is_old_variant(&image_path) {
+                    file_name.replacen(&slug_updater(old_slug), &slug_updater(&self.slug), 1)
+                } else {
+                    file_name
+                };
+                changes.push(Change::Rename {
+                    to: new_dir.join(new_name),
+                    from: image_path,
+                });
             }
             // Remove the old images directory
-            fs::remove_dir_all(old_image_path.parent().ok_or_else(|| {
-                ApcError::FileSystem(format!(
-                    "Post image should have a parent `{}`",
-                    old_image_path.display()
-                ))
-            })?)
-            .map_err(|err| ApcError::FileSystem(err.to_string()))?;
-
-            self.image_path = new_image_path;
+            changes.push(Change::RemoveDir(old_dir));
+        } else if is_new_header {
+            if new_header != old_header {
+                // The new header have another extension
+                changes.push(Change::RemoveFile(old_header));
+            }
+            // Remove the old variants that will not be replaced
+            for variant in old_images.into_iter().filter(|path| is_old_variant(path)) {
+                let is_replaced = header_changes.iter().any(
+                    |change| matches!(change, Change::WriteImage { path, .. } if *path == variant),
+                );
+                if !is_replaced {
+                    changes.push(Change::RemoveFile(variant));
+                }
+            }
+            changes.append(&mut header_changes);
         }
-        Ok(())
+        self.image_path = new_header.to_string_lossy().into_owned();
+        Ok(changes)
     }
 
+    /// Return the post properties keys and values, in the default order
+    pub fn properties(&self, config: &Config) -> Vec<(&'static str, Value)> {
+        vec![
+            ("title", self.title.clone().into()),
+            ("layout", self.layout.clone().into()),
+            (
+                "image",
+                self.image_path
+                    .replace(&config.images_path, &config.images_site_path)
+                    .into(),
+            ),
+            ("link", self.link.clone().into()),
+            (
+                "date",
+                self.date.format(&config.date_format).to_string().into(),
+            ),
+            (
+                "dateModified",
+                self.date_modified
+                    .format(&config.date_format)
+                    .to_string()
+                    .into(),
+            ),
+            ("description", self.description.clone().into()),
+            ("draft", self.is_draft.into()),
+            ("tags", self.tags.clone().into()),
+            // Removed if the post header doesn't have a placeholder
+            (
+                "imagePlaceholder",
+                self.image_placeholder
+                    .clone()
+                    .map_or(Value::Null, Value::from),
+            ),
+            // Removed if the post doesn't have header variants
+            (
+                "imageSrcset",
+                self.image_srcset.clone().map_or(Value::Null, Value::from),
+            ),
+            // Removed if the post header is not a card
+            (
+                "imageCard",
+                if self.image_card {
+                    Value::Bool(true)
+                } else {
+                    Value::Null
+                },
+            ),
+        ]
+    }
+
+    /// Return the post header.
+    /// If the post have a frontmatter from its file, only the changed properties will be updated
+    /// in their places, and the unknown keys, comments and order will be kept.
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
     pub fn full_properties(&self, config: &Config) -> String {
-        // FIXME: Remove a escape and use `#""#`.
-        // Use key value in format macro
-        format!(
-            "---\ntitle: \"{}\"\n\
-            layout: \"{}\"\n\
-            image: \"{}\"\n\
-            link: \"{}\"\n\
-            date: \"{}\"\n\
-            dateModified: \"{}\"\n\
-            description: \"{}\"\n\
-            draft: {}\n\
-            tags: {:?}\n\
-            ---\n\n\
-            # {}\n",
-            self.title,
-            self.layout,
-            self.image_path
-                .replace(&config.images_path, &config.images_site_path),
-            self.link,
-            self.date.format(&config.date_format),
-            self.date_modified.format(&config.date_format),
-            self.description,
-            self.is_draft,
-            self.tags,
-            self.title,
-        )
+        match &self.frontmatter {
+            Some(source) => {
+                let original: Mapping = serde_yaml::from_str(source).unwrap_or_default();
+                let source = self
+                    .properties(config)
+                    .into_iter()
+                    .filter(|(key, value)| original.get(key) != Some(value))
+                    .fold(source.clone(), |source, (key, value)| {
+                        if value.is_null() {
+                            frontmatter::remove(&source, key)
+                        } else {
+                            frontmatter::set(&source, key, &frontmatter::render(&value))
+                        }
+                    });
+                format!("---\n{source}---\n")
+            }
+            None => format!(
+                "---\n{}---\n\n# {}\n",
+                self.properties(config)
+                    .into_iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| format!("{key}: {}\n", frontmatter::render(&value)))
+                    .collect::<String>(),
+                self.title,
+            ),
+        }
     }
 
     /// Return str props
     #[logfn(Debug)]
     pub fn str_properties(&self, config: &'static Config) -> String {
         // parse_props` will return error if the properties are invalid syntax.
-        Self::parse_props(self.full_properties(config)).unwrap()
+        Self::parse_props(&self.full_properties(config), &self.path(config)).unwrap()
     }
 
     /// Return the post path
@@ -399,56 +573,220 @@ impl Post {
 
     /// Return the post content from file
     /// ## Notes:
-    /// - If the content is empty, will return empty string.
-    /// - If the content is not empty, will return the content without the properties.
+    /// - If the content is empty or the post file doesn't exist, will return empty string.
+    /// - If the content is not empty, will return the content after the `---` delimiters of the properties.
     ///
     /// ## Errors:
     /// - If cannot read the file.
+    /// - If the file doesn't start with properties.
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
     pub fn content(&self) -> ApcResult<String> {
         let config = CONFIG.as_ref().unwrap();
-        let content = fs::read_to_string(self.path(config))
-            .map_err(|err| ApcError::FileSystem(err.to_string()))?;
+        let path = self.path(config);
+        if !path.exists() {
+            // New post, there is no content yet
+            return Ok(String::new());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
 
-        Ok(content[self.full_properties(config).len()..]
-            .trim()
-            .to_string())
+        frontmatter::split(&content)
+            .map(|(_, body)| body.trim_start_matches(['\n', '\r']).trim_end().to_owned())
+            .ok_or_else(|| {
+                ApcError::PostProperties(format!(
+                    "'{}:1' the post should start with properties between `---` lines",
+                    path.display()
+                ))
+            })
     }
 
-    /// Write the post properties in file
+    /// Return the post file content, the properties with the `content`.
+    /// If the title is changed, the title heading in the start of the content will be updated.
     #[logfn(Debug)]
-    pub fn write_in_file(&self, config: &'static Config) -> ApcResult<()> {
-        fs::write(
-            self.path(config),
-            format!(
-                "{}\n{}",
-                self.full_properties(config),
-                self.content()?.trim()
-            ),
-        )
-        .map_err(|err| {
-            log::error!("{:?}", err);
-            ApcError::FileSystem(err.to_string())
-        })?;
+    pub fn file_content(&self, config: &Config, content: &str) -> String {
+        let mut content = content.to_owned();
+        let old_title = self
+            .frontmatter
+            .as_deref()
+            .and_then(|source| serde_yaml::from_str::<Mapping>(source).ok())
+            .and_then(|props| {
+                props
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned)
+            });
+        if let Some(old_title) = old_title.filter(|old_title| old_title != &self.title) {
+            if let Some(rest) = content
+                .strip_prefix(&format!("# {old_title}"))
+                .filter(|rest| rest.is_empty() || rest.starts_with(['\n', '\r']))
+            {
+                content = format!("# {}{rest}", self.title);
+            }
+        }
 
-        Ok(())
+        if content.is_empty() {
+            self.full_properties(config)
+        } else {
+            format!("{}\n{content}\n", self.full_properties(config))
+        }
     }
 
-    /// Update the file
+    /// Return the changes to copy the post header (or to write its header card) and create the post file, in order.
+    /// This method will update `image_path` to the copied header.
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
-    pub fn modify_post(&mut self, new_slug: String, new_image: String) -> ApcResult<()> {
-        let config = CONFIG.as_ref().unwrap();
+    pub fn create_changes(&mut self, config: &'static Config) -> ApcResult<Vec<Change>> {
+        let header = if self.header_card {
+            card_post_header(config, &self.slug, &self.title, &self.tags)?
+        } else {
+            copy_post_header(config, &self.slug, &self.image_path)?
+        };
+        let mut changes = header.changes;
+        self.image_path = header.path;
+        self.image_srcset = header.srcset;
+        self.image_placeholder = header.placeholder;
+        self.image_card = self.header_card;
+        changes.push(Change::Write {
+            path: self.path(config),
+            contents: self.file_content(config, &self.content()?),
+        });
+        Ok(changes)
+    }
+
+    /// Create the post file and copy its header to images directory
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn create_post(&mut self, config: &'static Config, dry_run: bool) -> ApcResult<()> {
+        let changes = self.create_changes(config)?;
+        Transaction::new(dry_run).apply(&changes)
+    }
+
+    /// Return the changes to modify the post file and its images, in order.
+    /// This method will update the post to the new slug and image.
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn modify_changes(
+        &mut self,
+        config: &'static Config,
+        new_slug: &str,
+        new_image: &str,
+    ) -> ApcResult<Vec<Change>> {
+        // Read the content before the slug changes
+        let content = self.content()?;
         let old_slug = self.slug.clone();
-        self.update_slug(config, &new_slug)?;
-        self.update_images(config, &old_slug)?;
-        if new_image != self.image_path {
-            fs::remove_file(&self.image_path)
-                .map_err(|err| ApcError::FileSystem(format!("`{}` {}", self.image_path, err)))?;
-            self.image_path = copy_post_header(config, &self.slug, &new_image)?;
+        let old_link = self.link.clone();
+        let old_path = self.path(config);
+        let mut changes = self.update_slug(config, new_slug);
+        changes.append(&mut self.update_images(config, &old_slug, new_image)?);
+        let content = if old_slug != self.slug {
+            links::rewrite(config, &content, &old_slug, &self.slug)
+        } else {
+            content
+        };
+        changes.push(Change::Write {
+            path: self.path(config),
+            contents: self.file_content(config, &content),
+        });
+        if old_slug != self.slug {
+            changes.append(&mut links::rewrite_changes(
+                config, &old_path, &old_slug, &self.slug,
+            )?);
+            changes.extend(redirects::redirect_change(config, &old_link, &self.link)?);
+        }
+        Ok(changes)
+    }
+
+    /// Update the file, all the changes will be rolled back if one of them failed.
+    /// The posts that link to the renamed post will be listed
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn modify_post(
+        &mut self,
+        new_slug: String,
+        new_image: String,
+        dry_run: bool,
+    ) -> ApcResult<()> {
+        let config = CONFIG.as_ref().unwrap();
+        let changes = self.modify_changes(config, &new_slug, &new_image)?;
+        Transaction::new(dry_run).apply(&changes)?;
+
+        let post_path = self.path(config);
+        let rewritten: Vec<_> = changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Write { path, .. }
+                    if *path != post_path && path.starts_with(&config.posts_path) =>
+                {
+                    Some(path)
+                }
+                _ => None,
+            })
+            .collect();
+        if !dry_run && !rewritten.is_empty() {
+            println!("The links to `{}` have been updated in:", self.slug);
+            for path in rewritten {
+                println!("  - {}", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the post file and its images directory, the existing ones of them.
+    /// If `trash_path` is configured, will move them to `trash_path/<slug>-<timestamp>/` instead of removing them.
+    /// ## Errors:
+    /// - If the post file and its images directory don't exist.
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn delete(&self, config: &'static Config, dry_run: bool) -> ApcResult<()> {
+        let post_path = self.path(config);
+        let images_dir = post_images_dir(config, &self.slug);
+        let mut changes = Vec::new();
+        if !post_path.exists() && !images_dir.exists() {
+            return Err(ApcError::Validation(format!(
+                "There is no post `{}` or images directory for it",
+                self.slug
+            )));
+        }
+
+        if let Some(trash_path) = &config.trash_path {
+            let trash_dir = Path::new(trash_path).join(format!(
+                "{}-{}",
+                slug_updater(&self.slug),
+                chrono::offset::Local::now().format("%Y%m%d%H%M%S")
+            ));
+            // The trash directory will be created with it, if it's not exists
+            changes.push(Change::CreateDir(trash_dir.clone()));
+            if post_path.exists() {
+                changes.push(Change::Rename {
+                    to: trash_dir.join(post_path.file_name().expect("The post path is a file")),
+                    from: post_path,
+                });
+            }
+            if images_dir.exists() {
+                changes.push(Change::Rename {
+                    from: images_dir,
+                    to: trash_dir.join("images"),
+                });
+            }
+            Transaction::new(dry_run).apply(&changes)?;
+            (!dry_run).then(|| {
+                println!(
+                    "The post `{}` has been moved to `{}`",
+                    self.slug,
+                    trash_dir.display()
+                )
+            });
+        } else {
+            if post_path.exists() {
+                changes.push(Change::RemoveFile(post_path));
+            }
+            if images_dir.exists() {
+                changes.push(Change::RemoveDir(images_dir));
+            }
+            Transaction::new(dry_run).apply(&changes)?;
+            (!dry_run).then(|| println!("The post `{}` has been deleted", self.slug));
         }
-        self.write_in_file(config)?;
         Ok(())
     }
 
@@ -462,27 +800,24 @@ impl Post {
         // FIXME: I don't think it's the best way to read large files
         fn inner(path: &Path) -> ApcResult<String> {
             Post::parse_props(
-                fs::read_to_string(path).map_err(|err| ApcError::FileSystem(err.to_string()))?,
+                &fs::read_to_string(path)
+                    .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?,
+                path,
             )
         }
         inner(post_path.as_ref())
     }
 
-    /// Parse full props to key val str props
-    fn parse_props(full_props: String) -> ApcResult<String> {
-        let re = regex::Regex::new(r"^---\n(\w*: ?.*\n){9}---").unwrap();
-        if re.is_match(&full_props) {
-            Ok(full_props
-                .lines()
-                .skip(1)
-                .take(9)
-                .collect::<Vec<&str>>()
-                .join("\n"))
-        } else {
-            Err(ApcError::PostProperties(
-                "Invalid post properties in ".to_owned(),
-            ))
-        }
+    /// Return the frontmatter of the post content (the YAML between the `---` delimiters)
+    fn parse_props(full_props: &str, path: &Path) -> ApcResult<String> {
+        frontmatter::split(full_props)
+            .map(|(props, _)| props.to_owned())
+            .ok_or_else(|| {
+                ApcError::PostProperties(format!(
+                    "'{}:1' the post should start with properties between `---` lines",
+                    path.display()
+                ))
+            })
     }
 
     /// Returns post by file properties
@@ -492,11 +827,9 @@ impl Post {
     where
         P: AsRef<Path> + std::fmt::Debug,
     {
-        let props = properties::Props::from_str(
-            Self::properties_from_file(&post_path)?.as_str(),
-            post_path,
-        )?;
-        Post::try_new(
+        let source = Self::properties_from_file(&post_path)?;
+        let props = properties::Props::from_str(&source, &post_path)?;
+        let mut post = Post::try_new(
             config,
             props.title,
             props.slug,
@@ -506,6 +839,62 @@ impl Post {
             props.image_path,
             props.date,
             props.modified_date,
-        )
+        )?;
+        // Keep the layout, the header variants and placeholder, and the frontmatter as they are in the file
+        let frontmatter = Frontmatter::parse(&source, post_path.as_ref())?;
+        if let Some(layout) = frontmatter.mapping().get("layout").and_then(Value::as_str) {
+            post.layout = layout.to_owned();
+        }
+        let string = |key: &str| {
+            frontmatter
+                .mapping()
+                .get(key)
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        };
+        post.image_srcset = string("imageSrcset");
+        post.image_placeholder = string("imagePlaceholder");
+        post.image_card = frontmatter
+            .mapping()
+            .get("imageCard")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        post.frontmatter = Some(source);
+        Ok(post)
+    }
+
+    /// Returns all the posts in `posts_path` with their paths, sorted by path.
+    /// A post that cannot be parsed will be returned with its error, so one broken post
+    /// doesn't stop the others.
+    /// ## Errors:
+    /// - If cannot read `posts_path` directory.
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn all(config: &'static Config) -> ApcResult<Vec<(PathBuf, ApcResult<Self>)>> {
+        Ok(Self::paths(config)?
+            .into_iter()
+            .map(|path| {
+                let post = Self::from_file(config, &path);
+                (path, post)
+            })
+            .collect())
+    }
+
+    /// Return the paths of the posts files in `posts_path`, sorted
+    /// ### Errors
+    /// * If cannot read the posts directory
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn paths(config: &'static Config) -> ApcResult<Vec<PathBuf>> {
+        let mut paths = fs::read_dir(&config.posts_path)
+            .map_err(|err| ApcError::FileSystem(format!("'{}' {}", config.posts_path, err)))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, std::io::Error>>()
+            .map_err(|err| {
+                ApcError::FileSystem(format!("File in `{}`: {}", config.posts_path, err))
+            })?;
+        paths.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"));
+        paths.sort();
+        Ok(paths)
     }
 }
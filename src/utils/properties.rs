@@ -19,15 +19,16 @@ use chrono::{offset::Local, DateTime};
 use requestty::Answers;
 
 use super::{
+    frontmatter::Frontmatter,
     helpers::{get_modifing_post, is_show_all_action},
-    parse_bool, parse_str_vec, parse_string, slug_updater, tags_updater, Post, PostProperties,
+    parse_bool, slug_updater, tags_updater, Post, PostProperties,
 };
 use crate::{
     errors::{ApcError, ApcResult},
     utils::parse_str_date,
     CONFIG,
 };
-use std::{collections::HashMap, fmt::Debug, path::Path};
+use std::{fmt::Debug, path::Path};
 
 #[derive(Debug)]
 pub struct Props {
@@ -96,27 +97,6 @@ pub trait ExtractProp<'a> {
             "Can't extract {prop_name:?}"
         )))
     }
-
-    fn extract_ref_prop(&'a self, prop_name: Self::NameType) -> ApcResult<&'a Self::Output> {
-        Err(ApcError::PostProperties(format!(
-            "Can't extract {prop_name:?}"
-        )))
-    }
-}
-
-impl<'a, UU: Debug> ExtractProp<'a> for HashMap<String, UU> {
-    type NameType = &'a str;
-    type Output = UU;
-
-    #[logfn_inputs(Info)]
-    #[logfn(Debug)]
-    fn extract_ref_prop(&'a self, prop_name: Self::NameType) -> ApcResult<&'a Self::Output> {
-        self.get(prop_name).ok_or_else(|| {
-            ApcError::PostProperties(format!(
-                "Post don't have `{prop_name}` properite, and it's should"
-            ))
-        })
-    }
 }
 
 impl<'a> ExtractProp<'a> for Answers {
@@ -217,33 +197,37 @@ impl Props {
         date: DateTime<Local>,
         modified_date: DateTime<Local>,
     ) -> ApcResult<Self> {
-        let config = CONFIG.as_ref().unwrap();
-        let image_path = image_site_path.replace(&config.images_site_path, &config.images_path);
+        Ok(Self::new(
+            slug.clone(),
+            title,
+            desctiption,
+            Self::image_path(&image_site_path)?,
+            tags,
+            draft,
+            date,
+            modified_date,
+        ))
+    }
 
+    /// Return the header path in `images_path` of the post image site path
+    /// ### Errors
+    /// * If the image doesn't start with `images_site_path`, or it doesn't exist
+    pub fn image_path(image_site_path: &str) -> ApcResult<String> {
+        let config = CONFIG.as_ref().unwrap();
         if !image_site_path.starts_with(&config.images_site_path) {
             return Err(ApcError::PostProperties(format!(
                 "The post image doesn't start with {} (its path from config)",
                 config.images_site_path
             )));
         }
-
+        let image_path = image_site_path.replace(&config.images_site_path, &config.images_path);
         if !Path::new(&image_path).exists() {
             return Err(ApcError::Validation(format!(
                 "The post image doesn't exist: {}",
                 image_path
             )));
         }
-
-        Ok(Self::new(
-            slug.clone(),
-            title,
-            desctiption,
-            image_path,
-            tags,
-            draft,
-            date,
-            modified_date,
-        ))
+        Ok(image_path)
     }
 
     /// Return modified props from answers
@@ -296,7 +280,10 @@ impl Props {
         }
     }
 
-    /// Parse a props from str
+    /// Parse a props from frontmatter source (the YAML between the `---` delimiters)
+    /// ### Errors
+    /// * If the frontmatter is invalid YAML, the error will have the file and line
+    /// * If a property is missing or have invalid value
     #[logfn(Debug)]
     #[logfn_inputs(Info)]
     pub fn from_str<P>(str_props: &str, post_path: P) -> ApcResult<Self>
@@ -304,80 +291,31 @@ impl Props {
         P: AsRef<Path> + std::fmt::Debug,
     {
         fn inner(props: &str, path: &Path) -> ApcResult<Props> {
-            let mut hash_props: HashMap<String, String> = HashMap::new();
             let config = CONFIG.as_ref().unwrap();
-            let keys = [
-                "title",
-                "layout",
-                "image",
-                "link",
-                "date",
-                "dateModified",
-                "description",
-                "draft",
-                "tags",
-            ];
-            for line in props.lines() {
-                let mut split_line = line.split(':');
-                if let Some(key) = split_line.next() {
-                    if keys.contains(&key) {
-                        if let Some(value) = split_line.next() {
-                            if !hash_props.contains_key(key) {
-                                hash_props.insert(key.to_owned(), value.trim().to_owned());
-                            } else {
-                                // duplicate key
-                                return Err(ApcError::PostProperties(format!(
-                                    "'{key}' i'ts duplicate key in {}",
-                                    path.display()
-                                )));
-                            }
-                        } else {
-                            // invalid line format
-                            return Err(ApcError::PostProperties(format!(
-                                "'{line}' invalid fomrat (key:value) in '{}'",
-                                path.display()
-                            )));
-                        }
-                    } else {
-                        // invalid key
-                        return Err(ApcError::PostProperties(format!(
-                            "'{key}' i'ts invalid key in {}",
-                            path.display()
-                        )));
-                    }
-                } else {
-                    // invalid line format
-                    return Err(ApcError::PostProperties(format!(
-                        "'{line}' invalid fomrat (key:value) in '{}'",
-                        path.display()
-                    )));
-                }
-            }
+            let frontmatter = Frontmatter::parse(props, path)?;
+            let date = |key: &str| {
+                parse_str_date(&frontmatter.string(key)?, &config.date_format)
+                    .map_err(|err| frontmatter.error(key, &err.to_string()))
+            };
+            let image = frontmatter.string("image")?;
+            Props::image_path(&image)
+                .map_err(|err| frontmatter.error("image", &err.to_string()))?;
 
             Props::try_new(
-                parse_string(hash_props.extract_ref_prop("link")?)?
+                frontmatter
+                    .string("link")?
                     .rsplit('/')
                     .next()
-                    .ok_or_else(|| {
-                        ApcError::PostProperties(format!(
-                            "'{}' have a invalid post link",
-                            path.display()
-                        ))
-                    })?
+                    .filter(|slug| !slug.is_empty())
+                    .ok_or_else(|| frontmatter.error("link", "is invalid post link"))?
                     .to_owned(),
-                parse_string(hash_props.extract_ref_prop("title")?)?,
-                parse_string(hash_props.extract_ref_prop("description")?)?,
-                parse_string(hash_props.extract_ref_prop("image")?)?,
-                parse_str_vec(hash_props.extract_ref_prop("tags")?)?,
-                parse_bool(hash_props.extract_ref_prop("draft")?)?,
-                parse_str_date(
-                    &parse_string(hash_props.extract_ref_prop("date")?)?,
-                    &config.date_format,
-                )?,
-                parse_str_date(
-                    &parse_string(hash_props.extract_ref_prop("dateModified")?)?,
-                    &config.date_format,
-                )?,
+                frontmatter.string("title")?,
+                frontmatter.string("description")?,
+                image,
+                frontmatter.strings("tags")?,
+                frontmatter.bool("draft")?,
+                date("date")?,
+                date("dateModified")?,
             )
         }
         inner(str_props, post_path.as_ref())
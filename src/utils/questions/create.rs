@@ -23,7 +23,7 @@ use requestty::Question;
 /// Returns the post title question
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn post_title_question(config: &'static Config) -> Question {
+fn post_title_question(config: &'static Config) -> Question<'static> {
     Question::input("post_title")
         .message(&config.create_post_settings.title_message)
         .validate_on_key(validators::is_valid_title_length(config))
@@ -36,7 +36,7 @@ fn post_title_question(config: &'static Config) -> Question {
 /// Returns the post description question
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn post_description_question(config: &'static Config) -> Question {
+fn post_description_question(config: &'static Config) -> Question<'static> {
     Question::input("post_description")
         .message(&config.create_post_settings.description_message)
         .validate_on_key(validators::is_valid_description_length(config))
@@ -49,7 +49,7 @@ fn post_description_question(config: &'static Config) -> Question {
 /// Returns the psst tags question
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn post_tags_question(config: &'static Config) -> Question {
+fn post_tags_question(config: &'static Config) -> Question<'static> {
     Question::input("post_tags")
         .message(&config.create_post_settings.tags_message)
         .validate_on_key(validators::is_valid_tags(config))
@@ -62,7 +62,7 @@ fn post_tags_question(config: &'static Config) -> Question {
 /// Returns the post slug question
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn post_slug_question(config: &'static Config) -> Question {
+fn post_slug_question(config: &'static Config) -> Question<'static> {
     Question::input("post_slug")
         .message(&config.create_post_settings.slug_message)
         .validate_on_key(helpers::join_on_key_validator(
@@ -78,29 +78,41 @@ fn post_slug_question(config: &'static Config) -> Question {
         .build()
 }
 
+/// Returns the post header card question
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn post_card_question(config: &'static Config) -> Question<'static> {
+    Question::confirm("post_card")
+        .message(&config.create_post_settings.card_message)
+        .default(false)
+        .when(helpers::is_new_post(config))
+        .build()
+}
+
 /// Returns the post image question
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn post_image_question(config: &'static Config) -> Question {
+fn post_image_question(config: &'static Config) -> Question<'static> {
     Question::input("post_image")
         .message(&config.create_post_settings.image_message)
         .validate_on_key(validators::is_valid_path(false))
-        .validate(validators::file_path_validator(false))
+        .validate(validators::header_image_validator(config))
         .transform(helpers::full_path_transform())
         .auto_complete(helpers::autocomplete_files(None, None, false))
-        .when(helpers::is_new_post(config))
+        .when(helpers::is_new_post_image(config))
         .build()
 }
 
 /// Return all properties questions of post
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-pub fn post_properties(config: &'static Config) -> Vec<Question> {
+pub fn post_properties(config: &'static Config) -> Vec<Question<'static>> {
     vec![
         post_title_question(config),
         post_description_question(config),
         post_tags_question(config),
         post_slug_question(config),
+        post_card_question(config),
         post_image_question(config),
     ]
 }
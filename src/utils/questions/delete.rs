@@ -0,0 +1,57 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use requestty::Question;
+
+use crate::config::Config;
+use crate::utils::{helpers, to_post_path, validators};
+
+/// Question for choice a post to delete it
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn ask_for_post(config: &'static Config) -> Question<'static> {
+    Question::input("delete_post_file")
+        .message(&config.delete_post_settings.post_name_question)
+        .validate_on_key(validators::is_exsiting_post_slug(config))
+        .validate(validators::validate_post_path_by_slug(config))
+        .transform(|slug, _, backend| write!(backend, "{}", to_post_path(config, slug)))
+        .auto_complete(helpers::autocomplete_files(
+            Some(&config.posts_path),
+            Some("md"),
+            true,
+        ))
+        .when(helpers::is_delete_post(config))
+        .build()
+}
+
+/// Confirm the deletion
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+fn confirm_delete(config: &'static Config) -> Question<'static> {
+    Question::confirm("delete_confirm")
+        .message(&config.delete_post_settings.confirm_question)
+        .default(false)
+        .when(helpers::is_delete_post(config))
+        .build()
+}
+
+/// Return all questions of delete post
+#[logfn(Debug)]
+#[logfn_inputs(Info)]
+pub fn delete_post_properties(config: &'static Config) -> Vec<Question<'static>> {
+    vec![ask_for_post(config), confirm_delete(config)]
+}
@@ -16,4 +16,5 @@
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod create;
+pub mod delete;
 pub mod modify;
@@ -23,7 +23,7 @@ use crate::utils::{helpers, slug_updater, to_post_path, validators, PostProperti
 /// Question for choice a post to modify it
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn ask_for_post(config: &'static Config) -> Question {
+fn ask_for_post(config: &'static Config) -> Question<'static> {
     Question::input("post_file")
         .message(&config.modify_post_settings.post_name_question)
         .validate_on_key(validators::is_exsiting_post_slug(config))
@@ -41,7 +41,7 @@ fn ask_for_post(config: &'static Config) -> Question {
 /// Choices to update the modified date
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn modify_action_choices(config: &'static Config) -> Question {
+fn modify_action_choices(config: &'static Config) -> Question<'static> {
     Question::multi_select("modify_action")
         .message(&config.modify_post_settings.choice_action)
         .choice_with_default(&config.modify_post_settings.update_the_date_question, true)
@@ -66,7 +66,7 @@ fn modify_action_choices(config: &'static Config) -> Question {
 /// Ask for new slug (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_slug(config: &'static Config) -> Question {
+fn new_slug(config: &'static Config) -> Question<'static> {
     Question::input("new_post_slug")
         .message(&config.modify_post_settings.new_post_slug)
         .default(&config.modify_post_settings.keep_old_value_message)
@@ -89,7 +89,7 @@ fn new_slug(config: &'static Config) -> Question {
 /// Ask for new title (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_title(config: &'static Config) -> Question {
+fn new_title(config: &'static Config) -> Question<'static> {
     Question::input("new_post_title")
         .message(&config.modify_post_settings.new_post_title)
         .default(&config.modify_post_settings.keep_old_value_message)
@@ -106,7 +106,7 @@ fn new_title(config: &'static Config) -> Question {
 /// Ask for new description (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_description(config: &'static Config) -> Question {
+fn new_description(config: &'static Config) -> Question<'static> {
     Question::input("new_post_description")
         .message(&config.modify_post_settings.new_post_descrioption)
         .default(&config.modify_post_settings.keep_old_value_message)
@@ -123,12 +123,12 @@ fn new_description(config: &'static Config) -> Question {
 /// Ask for new image (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_image(config: &'static Config) -> Question {
+fn new_image(config: &'static Config) -> Question<'static> {
     Question::input("new_post_image")
         .message(&config.modify_post_settings.new_post_image)
         .default(&config.modify_post_settings.keep_old_value_message)
         .validate_on_key(validators::is_valid_path(false))
-        .validate(validators::file_path_validator(false))
+        .validate(validators::header_image_validator(config))
         .transform(helpers::join_transform(
             helpers::default_value_transform(config, PostProperties::Image),
             helpers::full_path_transform(),
@@ -141,7 +141,7 @@ fn new_image(config: &'static Config) -> Question {
 /// Ask for new tags (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_tags(config: &'static Config) -> Question {
+fn new_tags(config: &'static Config) -> Question<'static> {
     Question::input("new_post_tags")
         .message(&config.modify_post_settings.new_post_tags)
         .default(&config.modify_post_settings.keep_old_value_message)
@@ -158,7 +158,7 @@ fn new_tags(config: &'static Config) -> Question {
 /// Ask for new draft stutus (Show all action)
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-fn new_draft(config: &'static Config) -> Question {
+fn new_draft(config: &'static Config) -> Question<'static> {
     Question::confirm("new_post_draft")
         .message(&config.modify_post_settings.new_post_draft)
         .when(helpers::is_show_all_action(config))
@@ -168,7 +168,7 @@ fn new_draft(config: &'static Config) -> Question {
 /// Return all properties questions of modify post
 #[logfn(Debug)]
 #[logfn_inputs(Info)]
-pub fn modify_post_properties(config: &'static Config) -> Vec<Question> {
+pub fn modify_post_properties(config: &'static Config) -> Vec<Question<'static>> {
     vec![
         ask_for_post(config),
         modify_action_choices(config),
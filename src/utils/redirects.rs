@@ -0,0 +1,85 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::transaction::Change;
+use crate::config::Config;
+use crate::errors::{ApcError, ApcResult};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The redirects of the renamed posts, the old link to the new one.
+/// It's the same format of Astro `redirects` option
+pub type Redirects = BTreeMap<String, String>;
+
+/// Read the redirects file, will return empty redirects if it's not exists
+/// ### Errors
+/// * If cannot read the file, or it's not a JSON object of strings
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn read(path: &Path) -> ApcResult<Redirects> {
+    if !path.exists() {
+        return Ok(Redirects::new());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|err| ApcError::FileSystem(format!("'{}' {err}", path.display())))?;
+    serde_json::from_str(&content).map_err(|err| {
+        ApcError::Validation(format!(
+            "Invalid redirects file '{}': {err}",
+            path.display()
+        ))
+    })
+}
+
+/// Add a redirect from the `from` link to the `to` link.
+/// The redirects to `from` will redirect to `to` directly, so the chains of renames collapse into one redirect,
+/// and the redirect from `to` will be removed because it's a post link now.
+#[logfn_inputs(Info)]
+pub fn add(redirects: &mut Redirects, from: &str, to: &str) {
+    for target in redirects.values_mut() {
+        if target == from {
+            *target = to.to_owned();
+        }
+    }
+    redirects.insert(from.to_owned(), to.to_owned());
+    redirects.remove(to);
+}
+
+/// Return the change to write the redirect of the renamed post in `redirects_path`,
+/// `None` if `redirects_path` is not configured
+/// ### Errors
+/// * If cannot read the redirects file
+#[logfn_inputs(Info)]
+#[logfn(Debug)]
+pub fn redirect_change(
+    config: &Config,
+    old_link: &str,
+    new_link: &str,
+) -> ApcResult<Option<Change>> {
+    let Some(redirects_path) = &config.redirects_path else {
+        return Ok(None);
+    };
+    let path = PathBuf::from(redirects_path);
+    let mut redirects = read(&path)?;
+    add(&mut redirects, old_link, new_link);
+    let contents = serde_json::to_string_pretty(&redirects)
+        .map_err(|err| ApcError::Other(format!("Cannot serialize the redirects: {err}")))?;
+    Ok(Some(Change::Write {
+        path,
+        contents: format!("{contents}\n"),
+    }))
+}
@@ -0,0 +1,405 @@
+// Simple CLI to create/modify aleecers post template
+//     Copyright (C) 2020-2022  TheAwiteb
+//     https://github.com/aleecers/Alepc
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::images::EncodedImage;
+use crate::errors::{ApcError, ApcResult};
+use colored::Colorize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A filesystem change to apply in a [`Transaction`]
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// Rename a file or directory, it will fail if `to` is already exists
+    Rename { from: PathBuf, to: PathBuf },
+    /// Create a directory with its missing parents, if it's not exists
+    CreateDir(PathBuf),
+    /// Copy a file, the existing `to` file will be replaced
+    Copy { from: PathBuf, to: PathBuf },
+    /// Write a file, the existing file will be replaced
+    Write { path: PathBuf, contents: String },
+    /// Write an encoded image, the existing file will be replaced
+    WriteImage { path: PathBuf, image: EncodedImage },
+    /// Remove a file
+    RemoveFile(PathBuf),
+    /// Remove a directory with its content
+    RemoveDir(PathBuf),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rename { from, to } => {
+                write!(f, "rename `{}` to `{}`", from.display(), to.display())
+            }
+            Self::CreateDir(path) => write!(f, "create directory `{}`", path.display()),
+            Self::Copy { from, to } => {
+                write!(f, "copy `{}` to `{}`", from.display(), to.display())
+            }
+            Self::Write { path, .. } => write!(f, "write `{}`", path.display()),
+            Self::WriteImage { path, image } => {
+                write!(f, "write image `{}` ({image})", path.display())
+            }
+            Self::RemoveFile(path) => write!(f, "remove file `{}`", path.display()),
+            Self::RemoveDir(path) => write!(f, "remove directory `{}`", path.display()),
+        }
+    }
+}
+
+/// An applied change, and what is needed to undo it
+#[derive(Debug)]
+enum Applied {
+    /// `from` was renamed to `to`
+    Rename { from: PathBuf, to: PathBuf },
+    /// The directory was created
+    CreateDir(PathBuf),
+    /// The file was created, and `backup` is the replaced file if any
+    CreateFile {
+        path: PathBuf,
+        backup: Option<PathBuf>,
+    },
+    /// The file or directory was removed, it's in `backup` until the commit
+    Remove { path: PathBuf, backup: PathBuf },
+}
+
+/// Map the IO error to [`ApcError::FileSystem`] with the path
+fn fs_error(path: &Path) -> impl FnOnce(std::io::Error) -> ApcError + '_ {
+    move |err| ApcError::FileSystem(format!("`{}` {err}", path.display()))
+}
+
+/// Return true if the error is because `rename` cannot move across filesystems (`EXDEV`)
+fn is_cross_device(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const CROSS_DEVICE: i32 = 18;
+    #[cfg(windows)]
+    const CROSS_DEVICE: i32 = 17;
+    #[cfg(not(any(unix, windows)))]
+    const CROSS_DEVICE: i32 = -1;
+    err.raw_os_error() == Some(CROSS_DEVICE)
+}
+
+/// Copy a file, or a directory with its content
+fn copy_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_all(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Remove a file, or a directory with its content
+fn remove_all(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Rename `from` to `to`, if they are in different filesystems (e.g. `trash_path` in `/tmp`)
+/// `from` will be copied then removed
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if is_cross_device(&err) => {
+            if let Err(err) = copy_all(from, to) {
+                remove_all(to).ok();
+                return Err(err);
+            }
+            // Move it aside in its filesystem first, so it's not half removed if the removing failed
+            let removed = sibling_path(from, "alepc-moved");
+            if let Err(err) = fs::rename(from, &removed) {
+                remove_all(to).ok();
+                return Err(err);
+            }
+            if let Err(err) = remove_all(&removed) {
+                log::warn!("Cannot remove `{}`: {err}", removed.display());
+            }
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Return a free hidden path next to `path` with `suffix`, e.g. `.post.md.alepc-backup`.
+/// It's in the same directory to make the rename atomic.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut sibling = parent.join(format!(".{name}.{suffix}"));
+    let mut count = 1;
+    while sibling.exists() {
+        sibling = parent.join(format!(".{name}.{suffix}{count}"));
+        count += 1;
+    }
+    sibling
+}
+
+/// Apply filesystem changes as one unit, if a change failed the applied changes will be rolled back.
+/// The removed and replaced files are kept as hidden backups until the commit.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    applied: Vec<Applied>,
+    /// Print the changes instead of applying them
+    dry_run: bool,
+}
+
+/// Return the unified diff of `old` and `new` file contents, colored
+fn diff(path: &Path, old: &str, new: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&path.to_string_lossy(), &path.to_string_lossy())
+        .to_string()
+        .lines()
+        .map(|line| {
+            if line.starts_with("+++") || line.starts_with("---") {
+                line.bold().to_string()
+            } else if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else if line.starts_with("@@") {
+                line.cyan().to_string()
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Transaction {
+    /// Create a new transaction, if `dry_run` is true the changes will be printed only
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ..Default::default()
+        }
+    }
+
+    /// Print the changes, with a diff of the written files
+    fn print_changes(changes: &[Change]) {
+        println!("{}", "Dry run, nothing will be changed:".yellow());
+        for (idx, change) in changes.iter().enumerate() {
+            println!("{}. {change}", idx + 1);
+            if let Change::Write { path, contents } = change {
+                // The file may be renamed by a previous change
+                let old_path = changes[..idx]
+                    .iter()
+                    .rev()
+                    .find_map(|change| match change {
+                        Change::Rename { from, to } if to == path => Some(from),
+                        _ => None,
+                    })
+                    .unwrap_or(path);
+                let old_contents = fs::read_to_string(old_path).unwrap_or_default();
+                if old_contents == *contents {
+                    println!("   (no changes)");
+                } else {
+                    println!("{}", diff(path, &old_contents, contents));
+                }
+            }
+        }
+    }
+
+    /// Move the existing `path` to a backup, and return the backup path
+    fn backup(path: &Path) -> ApcResult<Option<PathBuf>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let backup = sibling_path(path, "alepc-backup");
+        fs::rename(path, &backup).map_err(fs_error(path))?;
+        Ok(Some(backup))
+    }
+
+    /// Create a file by `create` in a temporary file, then move it to `path`
+    fn create_file(
+        &mut self,
+        path: &Path,
+        create: impl FnOnce(&Path) -> std::io::Result<()>,
+    ) -> ApcResult<()> {
+        let temp = sibling_path(path, "alepc-tmp");
+        if let Err(err) = create(&temp) {
+            fs::remove_file(&temp).ok();
+            return Err(fs_error(path)(err));
+        }
+        let backup = match Self::backup(path) {
+            Ok(backup) => backup,
+            Err(err) => {
+                fs::remove_file(&temp).ok();
+                return Err(err);
+            }
+        };
+        if let Err(err) = fs::rename(&temp, path) {
+            fs::remove_file(&temp).ok();
+            if let Some(backup) = &backup {
+                fs::rename(backup, path).ok();
+            }
+            return Err(fs_error(path)(err));
+        }
+        self.applied.push(Applied::CreateFile {
+            path: path.to_owned(),
+            backup,
+        });
+        Ok(())
+    }
+
+    /// Apply a single change
+    fn apply_change(&mut self, change: &Change) -> ApcResult<()> {
+        match change {
+            Change::Rename { from, to } => {
+                if to.exists() {
+                    return Err(ApcError::FileSystem(format!(
+                        "Cannot rename `{}`, `{}` is already exists",
+                        from.display(),
+                        to.display()
+                    )));
+                }
+                move_path(from, to).map_err(fs_error(from))?;
+                self.applied.push(Applied::Rename {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            Change::CreateDir(path) => {
+                // The top missing directory, removing it will remove the created ones in the rollback
+                if let Some(top) = path.ancestors().take_while(|dir| !dir.exists()).last() {
+                    fs::create_dir_all(path).map_err(fs_error(path))?;
+                    self.applied.push(Applied::CreateDir(top.to_owned()));
+                }
+            }
+            Change::Copy { from, to } => {
+                if !from.is_file() {
+                    return Err(ApcError::FileSystem(format!(
+                        "`{}` is not a file",
+                        from.display()
+                    )));
+                }
+                self.create_file(to, |temp| fs::copy(from, temp).map(|_| ()))?
+            }
+            Change::Write { path, contents } => {
+                self.create_file(path, |temp| fs::write(temp, contents))?
+            }
+            Change::WriteImage { path, image } => {
+                self.create_file(path, |temp| fs::write(temp, &image.bytes))?
+            }
+            Change::RemoveFile(path) | Change::RemoveDir(path) => {
+                let backup = Self::backup(path)?
+                    .ok_or_else(|| fs_error(path)(std::io::ErrorKind::NotFound.into()))?;
+                self.applied.push(Applied::Remove {
+                    path: path.clone(),
+                    backup,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo the applied changes in reverse order, and return a report of each one
+    fn rollback(self) -> Vec<String> {
+        self.applied
+            .into_iter()
+            .rev()
+            .map(|applied| {
+                let (result, message) = match &applied {
+                    Applied::Rename { from, to } => (
+                        move_path(to, from),
+                        format!("`{}` renamed back to `{}`", to.display(), from.display()),
+                    ),
+                    Applied::CreateDir(path) => (
+                        fs::remove_dir_all(path),
+                        format!("`{}` created directory removed", path.display()),
+                    ),
+                    Applied::CreateFile { path, backup } => (
+                        fs::remove_file(path).and_then(|_| {
+                            backup
+                                .as_ref()
+                                .map_or(Ok(()), |backup| fs::rename(backup, path))
+                        }),
+                        if backup.is_some() {
+                            format!("`{}` restored", path.display())
+                        } else {
+                            format!("`{}` created file removed", path.display())
+                        },
+                    ),
+                    Applied::Remove { path, backup } => (
+                        fs::rename(backup, path),
+                        format!("`{}` restored", path.display()),
+                    ),
+                };
+                match result {
+                    Ok(()) => message,
+                    Err(err) => format!("{} {message}: {err}", "Failed:".red()),
+                }
+            })
+            .collect()
+    }
+
+    /// Remove the backups of the applied changes
+    fn commit(self) {
+        for applied in self.applied {
+            let backup = match applied {
+                Applied::CreateFile {
+                    backup: Some(backup),
+                    ..
+                }
+                | Applied::Remove { backup, .. } => backup,
+                _ => continue,
+            };
+            if let Err(err) = remove_all(&backup) {
+                log::warn!("Cannot remove the backup `{}`: {err}", backup.display());
+            }
+        }
+    }
+
+    /// Apply the changes in order, if one of them failed the applied ones will be rolled back
+    /// and a report of what was restored will be printed. In dry run the changes will be printed only.
+    /// ## Errors:
+    /// - The error of the failed change
+    #[logfn(Debug)]
+    #[logfn_inputs(Info)]
+    pub fn apply(mut self, changes: &[Change]) -> ApcResult<()> {
+        if self.dry_run {
+            Self::print_changes(changes);
+            return Ok(());
+        }
+        for change in changes {
+            if let Err(err) = self.apply_change(change) {
+                log::error!("Cannot {change}: {err:?}");
+                let report = self.rollback();
+                if !report.is_empty() {
+                    eprintln!(
+                        "{}",
+                        "Cannot apply the changes, the applied ones have been rolled back:"
+                            .yellow()
+                    );
+                    report.iter().for_each(|line| eprintln!("  - {line}"));
+                }
+                return Err(err);
+            }
+        }
+        self.commit();
+        Ok(())
+    }
+}
@@ -15,13 +15,14 @@
 //     You should have received a copy of the GNU General Public License
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use super::{helpers, to_post_path};
+use super::images::HeaderInfo;
+use super::{check_slug_available, helpers, to_post_path};
 use crate::{
     config::Config,
     utils::{replace_tilde_with_home_dir, tags_updater},
 };
 use requestty::Answers;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Length validator
 pub fn length_validator(
@@ -103,6 +104,21 @@ pub fn tags_validator(config: &'static Config) -> impl FnMut(&str, &Answers) ->
     }
 }
 
+/// Header image validator, the header should exist and the header that will be stored
+/// (after processing it) should follow `header_image_rules`
+pub fn header_image_validator(
+    config: &'static Config,
+) -> impl FnMut(&str, &Answers) -> Result<(), String> {
+    helpers::join_str_validators(file_path_validator(false), move |str_path, _| {
+        HeaderInfo::processed(
+            &config.header_image_settings,
+            Path::new(&replace_tilde_with_home_dir(str_path)),
+        )
+        .map_err(|err| err.to_string())?
+        .check(&config.header_image_rules)
+    })
+}
+
 /// Make `is_exist` true if you want error when the file are existing
 pub fn file_path_validator(is_exist: bool) -> impl FnMut(&str, &Answers) -> Result<(), String> {
     move |str_path, _| {
@@ -165,7 +181,7 @@ pub fn description_length(
     config: &'static Config,
 ) -> impl FnMut(&str, &Answers) -> Result<(), String> {
     length_validator(
-        "post title",
+        "post description",
         config.create_post_settings.minimum_description_length,
         config.create_post_settings.maximum_description_length,
     )
@@ -194,7 +210,7 @@ pub fn is_valid_slug_length(config: &'static Config) -> impl FnMut(&str, &Answer
 pub fn slug_path_validator(
     config: &'static Config,
 ) -> impl FnMut(&str, &Answers) -> Result<(), String> {
-    move |slug, answers| file_path_validator(true)(&to_post_path(config, slug), answers)
+    move |slug, _| check_slug_available(config, slug).map_err(|err| err.to_string())
 }
 
 /// Is valid slug path